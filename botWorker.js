@@ -0,0 +1,36 @@
+import init, { JsInterface } from "./wasm/pkg/wasm.js";
+
+// Runs bot moves off the main thread so the page doesn't freeze while a bot
+// thinks. Receives { id, request: MoveRequest, stop?: Int32Array } and replies
+// with { id, progress: Progress } while the bot searches, then
+// { id, response: MoveResponse } or { id, error: string }.
+// `stop` is shared with the page, which sets it to 1 to make the bot play the
+// best move it has found so far.
+
+/** Least time between progress messages */
+const PROGRESS_INTERVAL_MS = 100;
+
+const ready = init();
+
+addEventListener("message", async (event) => {
+    await ready;
+
+    const { id, request, stop } = event.data;
+    let lastProgressTime = 0;
+    let lastDepth = 0;
+
+    try {
+        const response = JsInterface.run_move_request_with_progress(request, (progress) => {
+            const now = performance.now();
+            if (progress.depth !== lastDepth || now - lastProgressTime >= PROGRESS_INTERVAL_MS) {
+                postMessage({ id, progress });
+                lastProgressTime = now;
+                lastDepth = progress.depth;
+            }
+            return stop !== undefined && Atomics.load(stop, 0) !== 0;
+        });
+        postMessage({ id, response });
+    } catch (err) {
+        postMessage({ id, error: String(err) });
+    }
+});
@@ -0,0 +1,90 @@
+/**
+ * @typedef {{ board: string, color: boolean, bot: string, timeLimitMs?: number, variant?: "standard" | "antiOthello" }} MoveRequest
+ * @typedef {{ move: [number, number] | null, nodes: number | null, elapsedMs: number }} MoveResponse
+ * @typedef {{ depth: number, bestMove: [number, number] | null, nodes: number }} Progress
+ */
+
+/**
+ * Sends move requests to a Web Worker running the bots.
+ */
+export class BotWorker {
+    constructor() {
+        /** @type {Map<number, { resolve: (response: MoveResponse) => void, reject: (err: Error) => void, onProgress?: (progress: Progress) => void }>} */
+        this.pending = new Map();
+        this.nextId = 0;
+        this.worker = this.startWorker();
+        /**
+         * Set to 1 to stop the running search. Memory can only be shared
+         * with the worker when the page is cross-origin isolated.
+         * @type {Int32Array | undefined}
+         */
+        this.stopFlag = globalThis.crossOriginIsolated
+            ? new Int32Array(new SharedArrayBuffer(Int32Array.BYTES_PER_ELEMENT))
+            : undefined;
+    }
+
+    /** Whether `stop` can stop a running search */
+    get canStop() {
+        return this.stopFlag !== undefined;
+    }
+
+    /**
+     * Asks a bot for its move.
+     * @param {MoveRequest} request
+     * @param {(progress: Progress) => void} [onProgress] called while the bot searches
+     * @returns {Promise<MoveResponse>}
+     */
+    request(request, onProgress) {
+        const id = this.nextId++;
+        if (this.stopFlag) { Atomics.store(this.stopFlag, 0, 0); }
+        return new Promise((resolve, reject) => {
+            this.pending.set(id, { resolve, reject, onProgress });
+            this.worker.postMessage({ id, request, stop: this.stopFlag });
+        });
+    }
+
+    /**
+     * Makes the running searches finish now with the best move found so far.
+     * Their promises resolve as usual. Does nothing unless `canStop`.
+     */
+    stop() {
+        if (this.stopFlag) { Atomics.store(this.stopFlag, 0, 1); }
+    }
+
+    /**
+     * Stops all running requests, rejecting their promises.
+     * The worker is restarted since a running search can't be interrupted.
+     */
+    cancel() {
+        if (this.pending.size === 0) { return; }
+
+        this.worker.terminate();
+        for (const { reject } of this.pending.values()) {
+            reject(new Error("Cancelled"));
+        }
+        this.pending.clear();
+        this.worker = this.startWorker();
+    }
+
+    startWorker() {
+        const worker = new Worker(new URL("./botWorker.js", import.meta.url), { type: "module" });
+        worker.addEventListener("message", (event) => {
+            const { id, response, progress, error } = event.data;
+            const pending = this.pending.get(id);
+            if (!pending) { return; }
+
+            if (progress !== undefined) {
+                pending.onProgress?.(progress);
+                return;
+            }
+
+            this.pending.delete(id);
+            if (error !== undefined) {
+                pending.reject(new Error(error));
+            } else {
+                pending.resolve(response);
+            }
+        });
+        return worker;
+    }
+}
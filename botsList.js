@@ -1,14 +1,29 @@
-/** @type {Map<string, (jsi: import("./wasm/pkg/wasm").JsInterface) => void>} */
+/**
+ * Bot display names to the names used by `GameConfig.set_black_bot` and
+ * `GameConfig.set_white_bot`
+ * @type {Map<string, string>}
+ */
 export const botsMap = new Map([
-    ["Deep Negative Heuristic Bot", (jsi) => jsi.create_deep_negative_heuristic_bot()],
-    ["Center Bot", (jsi) => jsi.create_new_center_bot()],
-    ["Bottom Right Bot", (jsi) => jsi.create_new_last_valid_move_bot()],
-    ["Top Left Bot", (jsi) => jsi.create_new_first_valid_move_bot()],
-    ["Random Bot", (jsi) => jsi.create_new_random_bot()],
-    ["Shallow Score Bot", (jsi) => jsi.create_shallow_score_bot()],
-    ["Edge Bot", (jsi) => jsi.create_new_edge_bot()],
-    ["Edge Exclusive Bot", (jsi) => jsi.create_new_edge_exclusive_bot()],
-    ["Minmax Score Bot", (jsi) => jsi.create_new_minmax_score_bot()],
-    ["Deep Score Bot", (jsi) => jsi.create_deep_score_bot()],
-    ["Deep Heuristic Bot", (jsi) => jsi.create_deep_heuristic_bot()],
+    ["Deep Negative Heuristic Bot", "deep_negative_heuristic_bot"],
+    ["Center Bot", "center_bot"],
+    ["Bottom Right Bot", "last_valid_bot"],
+    ["Top Left Bot", "first_valid_bot"],
+    ["Random Bot", "random_bot"],
+    ["Shallow Score Bot", "shallow_score_bot"],
+    ["Edge Bot", "edge_bot"],
+    ["Edge Exclusive Bot", "edge_exclusive_bot"],
+    ["Minmax Score Bot", "minmax_score_bot"],
+    ["Deep Score Bot", "deep_score_bot"],
+    ["Deep Heuristic Bot", "deep_heuristic_bot"],
+    ["MCTS Bot", "mcts_bot"],
+    ["Level 1", "level_1"],
+    ["Level 2", "level_2"],
+    ["Level 3", "level_3"],
+    ["Level 4", "level_4"],
+    ["Level 5", "level_5"],
+    ["Level 6", "level_6"],
+    ["Level 7", "level_7"],
+    ["Level 8", "level_8"],
+    ["Level 9", "level_9"],
+    ["Level 10", "level_10"],
 ]);
@@ -1,6 +1,7 @@
-import init, { JsInterface } from "./wasm/pkg/wasm.js";
+import init, { GameConfig, JsInterface, Variant } from "./wasm/pkg/wasm.js";
 import { Elm } from "./elements.js";
 import { botsMap } from "./botsList.js";
+import { BotWorker } from "./botWorkerClient.js";
 
 /** @type {HTMLDivElement} */ // @ts-ignore
 const game = document.getElementById("game");
@@ -9,9 +10,35 @@ const history = new Elm(document.getElementById("history"));
 
 /** @type {HTMLButtonElement} */ // @ts-ignore
 const rerunButton = document.getElementById("rerunButton");
+/** @type {HTMLButtonElement} */ // @ts-ignore
+const moveNowButton = document.getElementById("moveNowButton");
+/** @type {Elm} */ // @ts-ignore
+const thinkingDisplay = new Elm(document.getElementById("thinking"));
+/** @type {HTMLButtonElement} */ // @ts-ignore
+const hintButton = document.getElementById("hintButton");
+/** @type {Elm} */ // @ts-ignore
+const hintDisplay = new Elm(document.getElementById("hint"));
+/** @type {HTMLButtonElement} */ // @ts-ignore
+const analyzeButton = document.getElementById("analyzeButton");
+/** @type {Elm} */ // @ts-ignore
+const analysisDisplay = new Elm(document.getElementById("analysis"));
+/** @type {HTMLButtonElement} */ // @ts-ignore
+const undoButton = document.getElementById("undoButton");
+/** @type {HTMLButtonElement} */ // @ts-ignore
+const redoButton = document.getElementById("redoButton");
 /** @type {HTMLDivElement} */ // @ts-ignore
 const sideElm = document.getElementById("side");
 
+/** @type {HTMLSelectElement} */ // @ts-ignore
+const variantSelect = document.getElementById("variantSelect");
+/** @type {HTMLSelectElement} */ // @ts-ignore
+const sizeSelect = document.getElementById("sizeSelect");
+/** @type {HTMLSelectElement} */ // @ts-ignore
+const handicapSelect = document.getElementById("handicapSelect");
+/** @type {HTMLInputElement} */ // @ts-ignore
+const blockedInput = document.getElementById("blockedInput");
+/** @type {HTMLSelectElement} */ // @ts-ignore
+const timeControlSelect = document.getElementById("timeControlSelect");
 /** @type {HTMLSelectElement} */ // @ts-ignore
 const blackBotSelect = document.getElementById("blackBotSelect");
 /** @type {HTMLSelectElement} */ // @ts-ignore
@@ -21,31 +48,126 @@ const numRoundsInput = document.getElementById("numRounds");
 
 /** @type {Elm[][]} */
 const boardCellContents = [];
+/** Squares along each side of the board that is drawn */
+let boardSize = 8;
 
 const lastBoardContents = [];
 
 /** @type {((x: number, y: number) => void)[]} */
 const clickListeners = [];
+/**
+ * Called with the cell under the pointer, or -1, -1 when it leaves the board
+ * @type {((x: number, y: number) => void)[]}
+ */
+const hoverListeners = [];
+/** @type {Elm[]} */
+let previewCells = [];
 
 const whiteScore = new Elm("span").class("score");
 const blackScore = new Elm("span").class("score");
+const whiteClock = new Elm("span").class("clock");
+const blackClock = new Elm("span").class("clock");
+const clockDisplay = new Elm("div");
 const gameOverDisplay = new Elm("div");
 const gameTableContainer = new Elm("div").class("gameTableContainer");
 
-/** @type {Map<string, (jsi: JsInterface) => void>} */
+/** Bots think in a worker so the page stays responsive */
+const botWorker = new BotWorker();
+/** Column letters for move notation */
+const letters = "abcdefghij";
+/** The bot whose moves are recommended as hints */
+const HINT_BOT = "deep_heuristic_bot";
+/** Plies searched for each position when analyzing a game */
+const ANALYSIS_DEPTH = 4;
+/** Longest time a bot may think for a move in games without clocks */
+const BOT_TIME_LIMIT_MS = 5000;
+/** Pause before a bot moves in games without clocks, so its moves can be followed */
+const BOT_MOVE_DELAY_MS = 300;
+/** How often the clocks are redrawn */
+const CLOCK_TICK_MS = 100;
+
+/**
+ * Player names to bot names, undefined for a human
+ * @type {Map<string, string | undefined>}
+ */
 const bots = new Map([
-    ["Human", (_) => { }],
+    ["Human", undefined],
 ]);
 
-for (const [name, fn] of botsMap) { bots.set(name, fn); }
+for (const [name, botName] of botsMap) { bots.set(name, botName); }
 
 const gameState = {
     whiteIsHuman: true,
     blackIsHuman: true,
+    /** @type {string | undefined} */
+    whiteBot: undefined,
+    /** @type {string | undefined} */
+    blackBot: undefined,
+    /** @type {"standard" | "antiOthello"} */
+    variant: "standard",
     gameActive: false,
-    turn: false
+    turn: false,
+    /** Id of the current game in the JsInterface, -1 before the first game */
+    gameId: -1,
+    /** @type {number | undefined} */
+    botMoveTimeout: undefined,
+    /**
+     * Latest progress of the bot that is thinking
+     * @type {import("./botWorkerClient.js").Progress | undefined}
+     */
+    botProgress: undefined,
+    /**
+     * The cell highlighted by a hint
+     * @type {Elm | undefined}
+     */
+    hintCell: undefined,
+    /**
+     * Analysis of the moves in the history, until another move is played
+     * @type {Report | undefined}
+     */
+    analysis: undefined
 };
 
+/**
+ * @typedef {object} MoveAnalysis
+ * @property {number} ply index in the history
+ * @property {boolean} color
+ * @property {[number, number]} move
+ * @property {[number, number]} bestMove
+ * @property {number} loss
+ * @property {"good" | "mistake" | "blunder"} judgement
+ *
+ * @typedef {object} Report
+ * @property {MoveAnalysis[]} moves
+ * @property {number | undefined} turningPoint
+ * @property {number} blackAccuracy
+ * @property {number} whiteAccuracy
+ */
+
+/**
+ * @typedef {object} Snapshot
+ * @property {number} size squares along each side
+ * @property {number[]} cells size * size cells, index y * size + x: 0 = empty; 1 = white; -1 = black; 2 = blocked
+ * @property {boolean | undefined} turn undefined if the game is over
+ * @property {number} blackCount
+ * @property {number} whiteCount
+ * @property {[number, number][]} legalMoves
+ * @property {[number, number] | undefined} lastMove
+ * @property {[number, number][]} lastFlipped
+ * @property {boolean} blackPassed
+ * @property {boolean} whitePassed
+ * @property {boolean} gameOver
+ * @property {"blackWins" | "whiteWins" | "draw" | undefined} result
+ */
+
+/**
+ * @typedef {Object} ClockState
+ * @property {number} blackMs
+ * @property {number} whiteMs
+ * @property {boolean | undefined} running the color whose clock is running
+ * @property {boolean | undefined} flagged the color that ran out of time
+ */
+
 function initAll() {
     initBotSelector();
     initBoard();
@@ -59,10 +181,37 @@ function initAll() {
             runGame(jsInterface);
         });
 
+        undoButton.addEventListener("click", () => {
+            if (jsInterface.undo(gameState.gameId)) { onHistoryChanged(jsInterface); }
+        });
+
+        hintButton.addEventListener("click", () => {
+            showHint(jsInterface);
+        });
+
+        analyzeButton.addEventListener("click", () => {
+            analyzeGame(jsInterface);
+        });
+
+        moveNowButton.addEventListener("click", () => {
+            moveNow(jsInterface);
+        });
+
+        redoButton.addEventListener("click", () => {
+            if (jsInterface.redo(gameState.gameId)) { onHistoryChanged(jsInterface); }
+        });
+
         clickListeners.push((x, y) => {
             humanInput(jsInterface, x, y);
-            renderBoard(jsInterface);
         });
+
+        hoverListeners.push((x, y) => {
+            previewMove(jsInterface, x, y);
+        });
+
+        setInterval(() => {
+            renderClocks(jsInterface);
+        }, CLOCK_TICK_MS);
     });
 }
 
@@ -78,17 +227,44 @@ function initBoard() {
         game.removeChild(game.lastChild);
     }
 
-    const table = new Elm("table").class("gameTable").appendTo(gameTableContainer.appendTo(game));
+    gameTableContainer.appendTo(game);
+    buildGameTable(boardSize);
+
+    new Elm().class("status").append(
+        new Elm().append("Score -- black: ", blackScore, ", white: ", whiteScore),
+        clockDisplay.append("Clock -- black: ", blackClock, ", white: ", whiteClock),
+        new Elm().append(gameOverDisplay)
+    ).appendTo(game);
+
+    // attach resize event handlers
+    updateGameCellSize();
+    addEventListener("resize", () => onResize());
+}
+
+/**
+ * (Re)builds the cells of the board for a board size
+ * @param {number} size
+ */
+function buildGameTable(size) {
+    gameTableContainer.clear();
+    boardSize = size;
+    boardCellContents.length = 0;
+    lastBoardContents.length = 0;
+    previewCells = [];
+
+    const table = new Elm("table").class("gameTable").appendTo(gameTableContainer);
     const tbody = new Elm("tbody").appendTo(table);
 
-    for (let y = 0; y < 8; y++) {
+    for (let y = 0; y < size; y++) {
         const boardRow = [];
         const tr = new Elm("tr").appendTo(tbody);
 
-        for (let x = 0; x < 8; x++) {
+        for (let x = 0; x < size; x++) {
             const cell = new Elm("td")
                 .class("gameCell")
                 .on("click", () => dispatchCellClicked(x, y))
+                .on("mouseenter", () => dispatchCellHovered(x, y))
+                .on("mouseleave", () => dispatchCellHovered(-1, -1))
                 .appendTo(tr);
 
             const content = new Elm().class("gameCellContent", "blank").appendTo(cell);
@@ -97,17 +273,10 @@ function initBoard() {
         }
 
         boardCellContents.push(boardRow);
-        lastBoardContents.push([0, 0, 0, 0, 0, 0, 0, 0]);
+        lastBoardContents.push(new Array(size).fill(0));
     }
 
-    new Elm().class("status").append(
-        new Elm().append("Score -- black: ", blackScore, ", white: ", whiteScore),
-        new Elm().append(gameOverDisplay)
-    ).appendTo(game);
-
-    // attach resize event handlers
     updateGameCellSize();
-    addEventListener("resize", () => onResize());
 }
 
 /**
@@ -124,38 +293,227 @@ function humanInput(jsi, x, y) {
         return;
     }
 
-    if (jsi.board_try_place(x, y, gameState.turn)) {
-        addHistory(gameState.turn, x, y);
-        gameState.turn = !gameState.turn;
-        checkGameState(jsi);
-
-        scheduleBotMoveIfShould(jsi);
+    try {
+        jsi.board_place(gameState.gameId, x, y, gameState.turn);
+    } catch (err) {
+        // not a valid move
+        return;
     }
+
+    clearAnalysis();
+    renderBoard(jsi);
+    renderHistory(jsi);
+    checkGameState(jsi);
+
+    scheduleBotMoveIfShould(jsi);
 }
 
 /** @param {JsInterface} jsi */
 function runBotMove(jsi) {
-    let moved = false;
-    let move;
-    if (gameState.turn === true && !gameState.whiteIsHuman) {
-        move = jsi.bot_run_white();
-        moved = true;
-    } else if (gameState.turn === false && !gameState.blackIsHuman) {
-        move = jsi.bot_run_black();
-        moved = true;
+    gameState.botMoveTimeout = undefined;
+    if (!gameState.gameActive) { return; }
+
+    const color = gameState.turn;
+    const bot = color ? gameState.whiteBot : gameState.blackBot;
+    if (bot === undefined) { return; }
+
+    const gameId = gameState.gameId;
+    moveNowButton.disabled = false;
+    thinkingDisplay.replaceContents("Thinking...");
+
+    botWorker.request({
+        board: jsi.get_board_text(gameId),
+        color,
+        bot,
+        timeLimitMs: jsi.get_move_time_ms(gameId) ?? BOT_TIME_LIMIT_MS,
+        variant: gameState.variant
+    }, progress => {
+        gameState.botProgress = progress;
+        renderThinking(progress);
+    }).then(response => {
+        // the game was restarted while the bot was thinking
+        if (gameId !== gameState.gameId) { return; }
+        playBotMove(jsi, color, response.move);
+    }, () => {
+        // cancelled
+    });
+}
+
+/**
+ * Stops the bot that is thinking and plays the best move it has found.
+ * @param {JsInterface} jsi
+ */
+function moveNow(jsi) {
+    if (botWorker.canStop) {
+        // the search ends early and its move is played as usual
+        botWorker.stop();
+        moveNowButton.disabled = true;
+        return;
     }
 
-    if (!moved) { return; }
+    // without shared memory the search can only be thrown away
+    const bestMove = gameState.botProgress?.bestMove;
+    if (!bestMove) { return; }
 
-    if (move) {
-        addHistory(gameState.turn, move[0], move[1]);
-        renderBoard(jsi);
-    } else {
-        addHistorySkipped(gameState.turn);
+    botWorker.cancel();
+    playBotMove(jsi, gameState.turn, bestMove);
+}
+
+/**
+ * @param {JsInterface} jsi
+ * @param {boolean} color
+ * @param {[number, number] | null} move
+ */
+function playBotMove(jsi, color, move) {
+    stopThinking();
+
+    try {
+        if (!move) { throw new Error("Bot has no moves"); }
+        jsi.board_place(gameState.gameId, move[0], move[1], color);
+    } catch (err) {
+        // the bot didn't make a valid move or ran out of time, so it forfeits
+        console.warn(err);
+        gameState.gameActive = false;
+        renderLegalMoves(jsi);
+        renderClocks(jsi);
+        return;
+    }
+
+    clearAnalysis();
+    renderBoard(jsi);
+    renderHistory(jsi);
+    checkGameState(jsi);
+    scheduleBotMoveIfShould(jsi);
+}
+
+/**
+ * Shows the recommended move for the human player whose turn it is.
+ * @param {JsInterface} jsi
+ */
+function showHint(jsi) {
+    if (!gameState.gameActive) { return; }
+    const isHumanTurn =
+        gameState.turn === true && gameState.whiteIsHuman ||
+        gameState.turn === false && gameState.blackIsHuman;
+    if (!isHumanTurn) { return; }
+
+    clearHint();
+    const hint = jsi.hint(gameState.gameId, gameState.turn, HINT_BOT);
+    if (!hint) { return; }
+
+    gameState.hintCell = boardCellContents[hint.move[1]][hint.move[0]];
+    gameState.hintCell.class("highlighted");
+    hintDisplay.replaceContents(`Hint: ${hint.notation} (${hint.explanation.join(", ")})`);
+}
+
+function isHumanTurn() {
+    return gameState.gameActive && (
+        gameState.turn === true && gameState.whiteIsHuman ||
+        gameState.turn === false && gameState.blackIsHuman
+    );
+}
+
+/**
+ * Marks the valid moves of a human player with dots.
+ * @param {JsInterface} jsi
+ */
+function renderLegalMoves(jsi) {
+    for (const row of boardCellContents.slice(0, boardSize)) {
+        for (const cell of row) { cell.removeClass("legal"); }
     }
-    gameState.turn = !gameState.turn;
+    if (!isHumanTurn()) { return; }
 
+    /** @type {[number, number][]} */
+    const moves = jsi.get_legal_moves(gameState.gameId);
+    for (const [x, y] of moves) {
+        boardCellContents[y][x].class("legal");
+    }
+}
+
+/**
+ * Fades the pieces a human player's move would flip while hovering over it.
+ * @param {JsInterface} jsi
+ * @param {number} x -1 to clear the preview
+ * @param {number} y
+ */
+function previewMove(jsi, x, y) {
+    for (const cell of previewCells) { cell.removeClass("preview"); }
+    previewCells = [];
+    if (x < 0 || !isHumanTurn()) { return; }
+
+    /** @type {[number, number][]} */
+    const flips = jsi.get_flips(gameState.gameId, x, y);
+    for (const [fx, fy] of flips) {
+        const cell = boardCellContents[fy][fx];
+        cell.class("preview");
+        previewCells.push(cell);
+    }
+}
 
+function clearHint() {
+    gameState.hintCell?.removeClass("highlighted");
+    gameState.hintCell = undefined;
+    hintDisplay.clear();
+}
+
+/**
+ * Marks mistakes, blunders and the turning point in the history list, and
+ * shows each player's accuracy.
+ * @param {JsInterface} jsi
+ */
+function analyzeGame(jsi) {
+    /** @type {Report} */
+    const report = jsi.analyze_game(gameState.gameId, ANALYSIS_DEPTH);
+    gameState.analysis = report;
+
+    const blunders = (color) => report.moves
+        .filter(analysis => analysis.color === color && analysis.judgement === "blunder")
+        .length;
+    analysisDisplay.replaceContents(
+        `Accuracy: black ${report.blackAccuracy.toFixed(1)}% (${blunders(false)} blunders), ` +
+        `white ${report.whiteAccuracy.toFixed(1)}% (${blunders(true)} blunders)`
+    );
+    renderHistory(jsi);
+}
+
+function clearAnalysis() {
+    gameState.analysis = undefined;
+    analysisDisplay.clear();
+}
+
+/** @param {import("./botWorkerClient.js").Progress} progress */
+function renderThinking(progress) {
+    const parts = ["Thinking..."];
+    if (progress.depth > 0) { parts.push(`depth ${progress.depth}`); }
+    if (progress.bestMove) {
+        parts.push("best " + letters[progress.bestMove[0]] + (progress.bestMove[1] + 1));
+    }
+    parts.push(`${progress.nodes} positions`);
+    thinkingDisplay.replaceContents(parts.join(", "));
+}
+
+function stopThinking() {
+    gameState.botProgress = undefined;
+    moveNowButton.disabled = true;
+    thinkingDisplay.clear();
+}
+
+/**
+ * Updates the page after moving through the history with undo, redo or
+ * clicking a history item.
+ * @param {JsInterface} jsi
+ */
+function onHistoryChanged(jsi) {
+    if (gameState.botMoveTimeout !== undefined) {
+        clearTimeout(gameState.botMoveTimeout);
+        gameState.botMoveTimeout = undefined;
+    }
+    botWorker.cancel();
+    stopThinking();
+
+    gameState.gameActive = true;
+    renderBoard(jsi);
+    renderHistory(jsi);
     checkGameState(jsi);
     scheduleBotMoveIfShould(jsi);
 }
@@ -166,13 +524,16 @@ function runBotMove(jsi) {
  */
 function scheduleBotMoveIfShould(jsi) {
     if (!gameState.gameActive) { return; }
+    if (gameState.botMoveTimeout !== undefined) { return; }
     if (
         gameState.turn === true && !gameState.whiteIsHuman ||
         gameState.turn === false && !gameState.blackIsHuman
     ) {
-        setTimeout(() => {
+        // in timed games the pause would come off the bot's clock
+        const delay = jsi.get_clock(gameState.gameId) ? 0 : BOT_MOVE_DELAY_MS;
+        gameState.botMoveTimeout = setTimeout(() => {
             runBotMove(jsi);
-        }, 300);
+        }, delay);
     }
 }
 
@@ -181,43 +542,54 @@ function scheduleBotMoveIfShould(jsi) {
  * @param {JsInterface} jsi
  */
 function checkGameState(jsi) {
-    if (!jsi.board_has_valid_move(gameState.turn)) {
-        addHistorySkipped(gameState.turn);
-        gameState.turn = !gameState.turn;
-
-        if (!jsi.board_has_valid_move(gameState.turn)) {
-            // game end
-            gameState.gameActive = false;
-            return;
-        }
+    const turn = jsi.get_turn(gameState.gameId);
+    if (turn === undefined) {
+        // game end
+        gameState.gameActive = false;
+    } else {
+        gameState.turn = turn;
     }
+    previewMove(jsi, -1, -1);
+    renderLegalMoves(jsi);
 }
 
-const letters = "abcdefgh";
-
 /**
- * @param {boolean} color 
- * @param {number} x 
- * @param {number} y 
+ * Rebuilds the history list. Clicking an item goes back to the position
+ * after that move.
+ * @param {JsInterface} jsi
  */
-function addHistory(color, x, y) {
-    _addHistory(color, `${letters[x]}${8 - y}`);
-}
+function renderHistory(jsi) {
+    history.clear();
 
-/** @param {boolean} color */
-function addHistorySkipped(color) {
-    _addHistory(color, `Skip`);
-}
+    const plies = jsi.get_history(gameState.gameId);
+    const startsWithWhite = jsi.get_start_turn(gameState.gameId) === true;
+    /** @type {Map<number, MoveAnalysis>} */
+    const analyses = new Map(gameState.analysis?.moves.map(analysis => [analysis.ply, analysis]));
+    for (let i = 0; i < plies.length; i++) {
+        // players alternate, and skips are part of the history
+        const color = (i % 2 === 1) !== startsWithWhite;
+        const text = plies[i] === "pass" ? "Skip" : plies[i];
+
+        const elm = new Elm("li").class("item");
+        if (color) { elm.class("white"); } else { elm.class("black"); }
+        elm.append(`${color ? "W" : "B"}: `, text);
+
+        const analysis = analyses.get(i);
+        if (analysis && analysis.judgement !== "good") {
+            elm.class(analysis.judgement);
+            const best = letters[analysis.bestMove[0]] + (analysis.bestMove[1] + 1);
+            elm.append(analysis.judgement === "blunder" ? "??" : "?", ` (best ${best})`);
+        }
+        if (i === gameState.analysis?.turningPoint) {
+            elm.class("turningPoint");
+            elm.append(" turning point");
+        }
+        elm.on("click", () => {
+            if (jsi.jump_to_ply(gameState.gameId, i + 1)) { onHistoryChanged(jsi); }
+        });
+        elm.appendTo(history);
+    }
 
-/**
- * @param {boolean} color 
- * @param {string} text 
- */
-function _addHistory(color, text) {
-    const elm = new Elm("li").class("item");
-    if (color) { elm.class("white"); } else { elm.class("black"); }
-    elm.append(`${color ? "W" : "B"}: `, text);
-    elm.appendTo(history);
     history.elm.scrollTop = history.elm.scrollHeight;
 }
 
@@ -226,27 +598,64 @@ function _addHistory(color, text) {
  */
 function runGame(jsInterface) {
     resetGameState();
-    bots.get(blackBotSelect.value)?.(jsInterface);
-    gameState.blackIsHuman = blackBotSelect.value === "Human";
-    jsInterface.set_bot_as_black();
-
-    bots.get(whiteBotSelect.value)?.(jsInterface);
-    gameState.whiteIsHuman = whiteBotSelect.value === "Human";
-    jsInterface.set_bot_as_white();
+    jsInterface.end_game(gameState.gameId);
+
+    const config = GameConfig.new();
+    const blackBot = bots.get(blackBotSelect.value);
+    const whiteBot = bots.get(whiteBotSelect.value);
+    gameState.blackIsHuman = blackBot === undefined;
+    gameState.whiteIsHuman = whiteBot === undefined;
+    gameState.blackBot = blackBot;
+    gameState.whiteBot = whiteBot;
+    if (blackBot !== undefined) { config.set_black_bot(blackBot); }
+    if (whiteBot !== undefined) { config.set_white_bot(whiteBot); }
+    // @ts-ignore
+    gameState.variant = variantSelect.value;
+    config.set_variant(gameState.variant === "antiOthello" ? Variant.AntiOthello : Variant.Standard);
+    config.set_size(parseInt(sizeSelect.value));
+    // handicap corners go to black, the usual weaker player
+    config.set_handicap(false, parseInt(handicapSelect.value));
+    if (blockedInput.value.trim()) { config.set_blocked_squares(blockedInput.value); }
+    // ex. "180000+2000" for 3 minutes plus 2 seconds a move, or "5000/move"
+    const timeControl = timeControlSelect.value;
+    if (timeControl.endsWith("/move")) {
+        config.set_time_per_move(parseInt(timeControl));
+    } else if (timeControl) {
+        const [totalMs, incrementMs] = timeControl.split("+").map(ms => parseInt(ms));
+        config.set_time_control(totalMs, incrementMs);
+    }
 
-    jsInterface.create_game();
+    try {
+        gameState.gameId = jsInterface.create_game(config);
+    } catch (err) {
+        // the blocked squares couldn't be used
+        gameOverDisplay.replaceContents(`Can't start the game: ${err.message}`);
+        return;
+    }
+    const size = jsInterface.get_board_size(gameState.gameId);
+    if (size !== boardSize) { buildGameTable(size); }
+    gameState.turn = jsInterface.get_turn(gameState.gameId) ?? false;
 
     // const numRounds = parseInt(numRoundsInput.value);
-    // console.log(jsInterface.bot_run_to_end_times(numRounds > 0 ? numRounds : 1));
+    // console.log(jsInterface.bot_run_to_end_times(gameState.gameId, numRounds > 0 ? numRounds : 1));
 
     renderBoard(jsInterface);
+    renderClocks(jsInterface);
     gameState.gameActive = true;
+    renderLegalMoves(jsInterface);
 
     // in case bot is first, run bot move
     runBotMove(jsInterface);
 }
 
 function resetGameState() {
+    if (gameState.botMoveTimeout !== undefined) {
+        clearTimeout(gameState.botMoveTimeout);
+        gameState.botMoveTimeout = undefined;
+    }
+    botWorker.cancel();
+    stopThinking();
+    clearAnalysis();
     gameState.turn = false;
     gameState.gameActive = false;
     gameState.blackIsHuman = true;
@@ -258,19 +667,27 @@ function resetGameState() {
  * @param {JsInterface} jsInterface
  */
 function renderBoard(jsInterface) {
-    const jsRep = getBoard(jsInterface);
-
-    for (let y = 0; y < 8; y++) {
-        for (let x = 0; x < 8; x++) {
-            if (jsRep[y][x] !== lastBoardContents[y][x]) {
-                const elm = boardCellContents[y][x];
-                renderGameCell(elm, jsRep[y][x]);
+    clearHint();
+    /** @type {Snapshot} */
+    const snapshot = jsInterface.get_snapshot(gameState.gameId);
+
+    for (let y = 0; y < snapshot.size; y++) {
+        for (let x = 0; x < snapshot.size; x++) {
+            const rep = snapshot.cells[y * snapshot.size + x];
+            const elm = boardCellContents[y][x];
+            if (rep !== lastBoardContents[y][x]) {
+                renderGameCell(elm, rep);
+                lastBoardContents[y][x] = rep;
             }
+            elm.removeClass("lastMove");
         }
     }
+    if (snapshot.lastMove) {
+        boardCellContents[snapshot.lastMove[1]][snapshot.lastMove[0]].class("lastMove");
+    }
 
-    const whites = jsInterface.board_count_pieces(true);
-    const blacks = jsInterface.board_count_pieces(false);
+    const whites = snapshot.whiteCount;
+    const blacks = snapshot.blackCount;
     whiteScore.replaceContents(whites.toString());
     blackScore.replaceContents(blacks.toString());
 
@@ -284,32 +701,102 @@ function renderBoard(jsInterface) {
         whiteScore.removeClass("leading");
         blackScore.removeClass("leading");
     }
+
+    if (snapshot.result) {
+        const results = { blackWins: "Black wins", whiteWins: "White wins", draw: "Draw" };
+        gameOverDisplay.replaceContents(`Game over: ${results[snapshot.result]}`);
+    } else if (snapshot.blackPassed || snapshot.whitePassed) {
+        gameOverDisplay.replaceContents(`${snapshot.blackPassed ? "Black" : "White"} has no moves and skips`);
+    } else {
+        gameOverDisplay.clear();
+    }
+}
+
+/**
+ * Draws the time left for each player, and ends the game if a player has
+ * run out of time.
+ * @param {JsInterface} jsi
+ */
+function renderClocks(jsi) {
+    /** @type {ClockState | undefined} */
+    let clock;
+    try {
+        clock = jsi.get_clock(gameState.gameId);
+    } catch (err) {
+        // the game couldn't be started
+    }
+    clockDisplay.elm.hidden = !clock;
+    if (!clock) { return; }
+
+    for (const [elm, ms, color] of [[blackClock, clock.blackMs, false], [whiteClock, clock.whiteMs, true]]) {
+        elm.replaceContents(formatClock(ms));
+        elm.removeClass("running");
+        elm.removeClass("flagged");
+        if (clock.running === color && gameState.gameActive) { elm.class("running"); }
+        if (clock.flagged === color) { elm.class("flagged"); }
+    }
+
+    if (clock.flagged === undefined) { return; }
+
+    // the game may already have been stopped, ex. by a bot's move failing
+    if (gameState.gameActive) {
+        if (gameState.botMoveTimeout !== undefined) {
+            clearTimeout(gameState.botMoveTimeout);
+            gameState.botMoveTimeout = undefined;
+        }
+        botWorker.cancel();
+        stopThinking();
+        gameState.gameActive = false;
+        renderLegalMoves(jsi);
+    }
+    const loser = clock.flagged ? "White" : "Black";
+    gameOverDisplay.replaceContents(`Game over: ${loser} ran out of time`);
+}
+
+/**
+ * @param {number} ms
+ * @returns {string} ex. "2:05.3"
+ */
+function formatClock(ms) {
+    const minutes = Math.floor(ms / 60000);
+    const seconds = ((ms % 60000) / 1000).toFixed(1).padStart(4, "0");
+    return `${minutes}:${seconds}`;
 }
 
 /**
  * @param {Elm} cell 
- * @param {bigint} rep 
+ * @param {number} rep 0 = empty; 1 = white; -1 = black
  */
 function renderGameCell(cell, rep) {
     switch (rep) {
-        case 0n:
+        case 0:
             cell.removeClass("black")
             cell.removeClass("white");
+            cell.removeClass("blocked");
             cell.class("blank");
             cell.clear();
             break;
-        case 1n:
+        case 1:
             cell.removeClass("black")
             cell.class("white");
             cell.removeClass("blank");
+            cell.removeClass("blocked");
             cell.replaceContents("W");
             break;
-        case -1n:
+        case -1:
             cell.class("black")
             cell.removeClass("white");
+            cell.removeClass("blocked");
             cell.removeClass("blank");
             cell.replaceContents("B");
             break;
+        case 2:
+            cell.removeClass("black")
+            cell.removeClass("white");
+            cell.removeClass("blank");
+            cell.class("blocked");
+            cell.clear();
+            break;
     }
 }
 
@@ -323,28 +810,14 @@ function dispatchCellClicked(x, y) {
     }
 }
 
-/** @param {JsInterface} jsInterface */
-function getBoard(jsInterface) {
-    let filled = jsInterface.get_board_filled();
-    let color = jsInterface.get_board_color();
-
-    const arr = [];
-    for (let i = 0; i < 8; i++) {
-        arr.push([
-            (filled & 1n) * (((color & 1n) << 1n) - 1n),
-            (filled >> 1n & 1n) * (((color >> 1n & 1n) << 1n) - 1n),
-            (filled >> 2n & 1n) * (((color >> 2n & 1n) << 1n) - 1n),
-            (filled >> 3n & 1n) * (((color >> 3n & 1n) << 1n) - 1n),
-            (filled >> 4n & 1n) * (((color >> 4n & 1n) << 1n) - 1n),
-            (filled >> 5n & 1n) * (((color >> 5n & 1n) << 1n) - 1n),
-            (filled >> 6n & 1n) * (((color >> 6n & 1n) << 1n) - 1n),
-            (filled >> 7n & 1n) * (((color >> 7n & 1n) << 1n) - 1n),
-        ]);
-        filled >>= 8n;
-        color >>= 8n;
+/**
+ * @param {number} x
+ * @param {number} y
+ */
+function dispatchCellHovered(x, y) {
+    for (const listener of hoverListeners) {
+        listener(x, y);
     }
-
-    return arr;
 }
 
 let lastWidth = innerWidth;
@@ -388,7 +861,7 @@ function updateGameCellSize() {
 
     let availableSize = Math.min(estimateAvailableWidth(), innerHeight * 0.8) - 1;
 
-    const spacePerCell = availableSize / 8;
+    const spacePerCell = availableSize / boardSize;
     const cellSize = spacePerCell - 1; // 1: cell border width
 
     const cellContentSize = cellSize * 0.8;
@@ -423,11 +896,11 @@ function createExplainOthelloBoard(text) {
 
     const textArr = text.split("\n").filter(x => x).map(x => x.split(" ").filter(x => x));
     const mappings = {
-        'x': -1n,
-        'X': -1n,
-        'o': 1n,
-        'O': 1n,
-        '_': 0n
+        'x': -1,
+        'X': -1,
+        'o': 1,
+        'O': 1,
+        '_': 0
     };
 
     for (let y = 0; y < textArr.length; y++) {
@@ -441,7 +914,7 @@ function createExplainOthelloBoard(text) {
 
             const content = new Elm().class("gameCellContent", "blank").appendTo(cell);
 
-            renderGameCell(content, mappings[textArr[y][x]] ?? 0n);
+            renderGameCell(content, mappings[textArr[y][x]] ?? 0);
 
             if (['X', 'O'].includes(textArr[y][x])) {
                 content.class("highlighted");
@@ -1,8 +1,15 @@
 import { botsMap } from "./botsList.js";
-import init, { JsInterface } from "./wasm/pkg/wasm.js";
+import init, { GameConfig, JsInterface, Variant } from "./wasm/pkg/wasm.js";
 
 // runs a tournament with the bots
 
+/** The rules the tournament is played by */
+const VARIANT = Variant.Standard;
+/** Corners given to black in every game, 0 for even games */
+const HANDICAP = 0;
+/** Squares blocked in every game, ex. "a1 h8", empty for none */
+const BLOCKED_SQUARES = "";
+
 init().then(() => {
     const jsi = JsInterface.new();
 
@@ -22,21 +29,33 @@ init().then(() => {
 
         for (const blackPlayer of botList) {
             for (const whitePlayer of botList) {
-                botsMap.get(blackPlayer)?.(jsi);
-                jsi.set_bot_as_black();
-
-                botsMap.get(whitePlayer)?.(jsi);
-                jsi.set_bot_as_white();
-
-                jsi.create_game();
-                jsi.bot_run_to_end();
+                const config = GameConfig.new();
+                config.set_black_bot(/** @type {string} */(botsMap.get(blackPlayer)));
+                config.set_white_bot(/** @type {string} */(botsMap.get(whitePlayer)));
+                config.set_variant(VARIANT);
+                config.set_handicap(false, HANDICAP);
+                if (BLOCKED_SQUARES) { config.set_blocked_squares(BLOCKED_SQUARES); }
+
+                const game = jsi.create_game(config);
+                let forfeited;
+                try {
+                    jsi.bot_run_to_end(game);
+                } catch (err) {
+                    // the bot whose turn it is made an invalid move
+                    forfeited = jsi.get_turn(game);
+                }
 
-                let whiteScore = jsi.board_count_pieces(true);
-                let blackScore = jsi.board_count_pieces(false);
+                // the winner under the tournament's rules
+                const result = jsi.get_snapshot(game).result;
+                jsi.end_game(game);
 
-                if (whiteScore > blackScore) {
+                if (forfeited === true) {
+                    win(elos, blackPlayer, whitePlayer);
+                } else if (forfeited === false) {
+                    win(elos, whitePlayer, blackPlayer);
+                } else if (result === "whiteWins") {
                     win(elos, whitePlayer, blackPlayer);
-                } else if (whiteScore < blackScore) {
+                } else if (result === "blackWins") {
                     win(elos, blackPlayer, whitePlayer);
                 } else {
                     draw(elos, blackPlayer, whitePlayer);
@@ -0,0 +1,123 @@
+//! Benchmarks for move generation and bot thinking time.
+//!
+//! Run with `cargo bench`. Bots that search also report their speed in
+//! nodes (positions looked at) per second.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use wasm::{
+    board::Board,
+    bots::{BOT_NAMES, create_bot, deep_heuristic_bot},
+    notation,
+    variant::Variant,
+};
+
+/// 20 moves in, black to move
+const MIDGAME: &str = "c4c3c2e3d3b1f4c5c6g4b5c7f5a5g3g5d6g2b4f6";
+/// 48 moves in, black to move
+const ENDGAME: &str = "c4c3c2e3d3b1f4c5c6g4b5c7f5a5g3g5d6g2b4f6b6a7h5b3g1h2f2f3e2g6e7d8a4b7c8a3b8d2d1d7f1e1a2h1c1b2a6a8";
+
+/// Searches averaged to give a bot's throughput
+const SIZE_SEARCHES: u64 = 10;
+
+/// Plays a game record from the starting position. Returns the board and
+/// the color to move.
+fn position(record: &str) -> (Board, bool) {
+    let mut board = Board::new();
+    let mut color = false;
+
+    for m in notation::parse_moves(record).expect("record is valid") {
+        assert!(board.try_place_chip(m.0, m.1, color));
+        color = board.next_turn(color).expect("game is not over");
+    }
+
+    (board, color)
+}
+
+fn positions() -> [(&'static str, Board, bool); 3] {
+    let (midgame, midgame_color) = position(MIDGAME);
+    let (endgame, endgame_color) = position(ENDGAME);
+    [
+        ("start", Board::new(), false),
+        ("midgame", midgame, midgame_color),
+        ("endgame", endgame, endgame_color),
+    ]
+}
+
+fn board_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("board");
+
+    for (name, board, color) in positions() {
+        group.bench_with_input(
+            BenchmarkId::new("get_all_valid_moves", name),
+            &board,
+            |b, board| b.iter(|| black_box(board).get_all_valid_moves(color)),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("has_valid_move", name),
+            &board,
+            |b, board| b.iter(|| black_box(board).has_valid_move(color)),
+        );
+
+        let m = board.get_all_valid_moves(color)[0];
+        group.bench_with_input(
+            BenchmarkId::new("try_place_chip", name),
+            &board,
+            |b, board| {
+                b.iter(|| {
+                    let mut future = black_box(board).clone();
+                    future.try_place_chip(m.0, m.1, color)
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("heuristic_score", name),
+            &board,
+            |b, board| {
+                b.iter(|| {
+                    deep_heuristic_bot::heuristic_score(black_box(board), color, Variant::Standard)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bot_benchmarks(c: &mut Criterion) {
+    let [_, midgame, endgame] = positions();
+
+    for bot_name in BOT_NAMES {
+        let mut bot = create_bot(bot_name).unwrap();
+        let mut group = c.benchmark_group(format!("make_move/{bot_name}"));
+        group.sample_size(10);
+
+        for (name, board, color) in [&midgame, &endgame] {
+            // MCTS playouts vary in length, and the level bots sometimes
+            // play a random move without searching, so the throughput is
+            // measured against the average size of a few searches
+            let sizes: Option<Vec<u64>> = (0..SIZE_SEARCHES)
+                .map(|_| {
+                    bot.make_move(board, *color);
+                    bot.nodes_searched()
+                })
+                .collect();
+            if let Some(sizes) = sizes {
+                let average = sizes.iter().sum::<u64>() / SIZE_SEARCHES;
+                group.throughput(Throughput::Elements(average));
+            }
+
+            group.bench_with_input(BenchmarkId::from_parameter(name), board, |b, board| {
+                b.iter(|| bot.make_move(black_box(board), *color))
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, board_benchmarks, bot_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,315 @@
+//! Post-game analysis: every move is compared with the best move the
+//! deep heuristic search can find, to point out mistakes and blunders.
+//!
+//! Scores are in the units of `deep_heuristic_bot::heuristic_score`, where
+//! a disc is worth 1 and a corner 10 to 20.
+
+use serde::Serialize;
+
+use crate::{
+    bots::deep_heuristic_bot::evaluate_board,
+    error::OthelloError,
+    game::{Game, Ply},
+    notation,
+    position::Position,
+    search::Search,
+    variant::Variant,
+};
+
+/// Moves losing at least this much score are mistakes
+pub const MISTAKE_LOSS: f32 = 4.0;
+/// Moves losing at least this much score are blunders
+pub const BLUNDER_LOSS: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Judgement {
+    Good,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    fn from_loss(loss: f32) -> Judgement {
+        if loss >= BLUNDER_LOSS {
+            Judgement::Blunder
+        } else if loss >= MISTAKE_LOSS {
+            Judgement::Mistake
+        } else {
+            Judgement::Good
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveAnalysis {
+    /// Index of the move in the game history, counting skips
+    pub ply: usize,
+    pub color: bool,
+    #[serde(rename = "move")]
+    pub m: (i8, i8),
+    pub best_move: (i8, i8),
+    /// Score of the played move, for the player who played it
+    pub score: f32,
+    pub best_score: f32,
+    /// How much worse the played move is than the best move, at least 0
+    pub loss: f32,
+    pub judgement: Judgement,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    /// One entry for each move, skips are left out
+    pub moves: Vec<MoveAnalysis>,
+    /// Ply of the move that lost the most score for the player who lost the
+    /// game, or for either player in a draw. None if no move lost score.
+    pub turning_point: Option<usize>,
+    /// 0 to 100, see `accuracy`
+    pub black_accuracy: f32,
+    pub white_accuracy: f32,
+}
+
+/// Analyzes the moves of a game, searching `depth` plies deep from each
+/// position
+pub fn analyze_game<B: Position>(game: &Game<B>, depth: u16) -> Report {
+    analyze_plies(game.start(), game.history(), depth, game.variant())
+}
+
+/// Analyzes a game record written in notation, ex. "f5d6c3". Skips are not
+/// written.
+pub fn analyze_record(record: &str, depth: u16, variant: Variant) -> Result<Report, OthelloError> {
+    let moves = notation::parse_moves(record).ok_or(OthelloError::InvalidRecord)?;
+
+    let mut game = Game::with_variant(variant);
+    for (x, y) in moves {
+        let color = game.turn().ok_or(OthelloError::GameOver)?;
+        game.play(x, y, color)?;
+    }
+
+    Ok(analyze_game(&game, depth))
+}
+
+/// Scores `m` against the best move for `color`, searching `depth` plies deep
+pub fn analyze_move<B: Position>(
+    board: &B,
+    color: bool,
+    m: (i8, i8),
+    depth: u16,
+    variant: Variant,
+) -> MoveAnalysis {
+    let depth = depth.max(1);
+    let mut search = Search::new(None, None);
+    let mut score_move = |m: (i8, i8)| {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        evaluate_board(
+            &future,
+            color,
+            !color,
+            depth - 1,
+            f32::MIN,
+            f32::MAX,
+            variant,
+            &mut search,
+        )
+    };
+
+    let score = score_move(m);
+    let (best_move, best_score) = board
+        .get_all_valid_moves(color)
+        .into_iter()
+        .map(|m| (m, score_move(m)))
+        .fold(
+            (m, score),
+            |best, curr| {
+                if curr.1 > best.1 { curr } else { best }
+            },
+        );
+
+    let loss = best_score - score;
+    MoveAnalysis {
+        ply: 0,
+        color,
+        m,
+        best_move,
+        score,
+        best_score,
+        loss,
+        judgement: Judgement::from_loss(loss),
+    }
+}
+
+/// Scores every valid move for `color`, searching `depth` plies deep, best
+/// first. Moves with the same score stay in board order.
+///
+/// Returns None if `search` was stopped before every move was scored.
+pub fn rank_moves<B: Position>(
+    board: &B,
+    color: bool,
+    depth: u16,
+    variant: Variant,
+    search: &mut Search,
+) -> Option<Vec<((i8, i8), f32)>> {
+    let depth = depth.max(1);
+    let mut ranked = Vec::new();
+    for m in board.get_all_valid_moves(color) {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        let score = evaluate_board(
+            &future,
+            color,
+            !color,
+            depth - 1,
+            f32::MIN,
+            f32::MAX,
+            variant,
+            search,
+        );
+        if search.stopped() {
+            return None;
+        }
+        ranked.push((m, score));
+    }
+
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Some(ranked)
+}
+
+/// Average accuracy of a player's moves, from 0 to 100. The best move
+/// counts as 100 and moves count for less the more score they lose.
+pub fn accuracy(moves: &[MoveAnalysis], color: bool) -> f32 {
+    let scores: Vec<f32> = moves
+        .iter()
+        .filter(|analysis| analysis.color == color)
+        .map(|analysis| 100.0 * (-analysis.loss / MISTAKE_LOSS).exp())
+        .collect();
+
+    if scores.is_empty() {
+        return 100.0;
+    }
+    scores.iter().sum::<f32>() / scores.len() as f32
+}
+
+fn analyze_plies<B: Position>(start: &B, plies: &[Ply], depth: u16, variant: Variant) -> Report {
+    let mut board = start.clone();
+    let mut moves = Vec::new();
+
+    for (ply, Ply { color, m }) in plies.iter().enumerate() {
+        let Some(m) = *m else {
+            continue;
+        };
+
+        moves.push(MoveAnalysis {
+            ply,
+            ..analyze_move(&board, *color, m, depth, variant)
+        });
+        board.try_place_chip(m.0, m.1, *color);
+    }
+
+    let loser = variant.winner(&board).map(|winner| !winner);
+
+    let turning_point = moves
+        .iter()
+        .filter(|analysis| loser.is_none_or(|loser| analysis.color == loser))
+        .filter(|analysis| analysis.loss > 0.0)
+        .max_by(|a, b| a.loss.total_cmp(&b.loss))
+        .map(|analysis| analysis.ply);
+
+    Report {
+        black_accuracy: accuracy(&moves, false),
+        white_accuracy: accuracy(&moves, true),
+        moves,
+        turning_point,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, create_board};
+
+    #[test]
+    fn missing_a_corner_is_a_blunder() {
+        // black can take the corner or play next to it
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        let corner = analyze_move(&board, false, (0, 0), 2, Variant::Standard);
+        assert_eq!(corner.best_move, (0, 0));
+        assert_eq!(corner.loss, 0.0);
+        assert_eq!(corner.judgement, Judgement::Good);
+
+        let x_square = analyze_move(&board, false, (1, 1), 2, Variant::Standard);
+        assert_eq!(x_square.best_move, (0, 0));
+        assert_eq!(x_square.judgement, Judgement::Blunder);
+        assert_eq!(accuracy(std::slice::from_ref(&corner), false), 100.0);
+        assert!(accuracy(&[corner, x_square], false) < 60.0);
+    }
+
+    #[test]
+    fn ranked_moves() {
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        let mut search = Search::new(None, None);
+        let ranked = rank_moves(&board, false, 2, Variant::Standard, &mut search).unwrap();
+        assert_eq!(ranked.len(), board.get_all_valid_moves(false).len());
+        assert_eq!(ranked[0].0, (0, 0));
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn game_record() {
+        let report = analyze_record("f5d6c3d3c4f4f6f3e6e7", 3, Variant::Standard).unwrap();
+        assert_eq!(report.moves.len(), 10);
+
+        for (i, analysis) in report.moves.iter().enumerate() {
+            assert_eq!(analysis.ply, i);
+            assert_eq!(analysis.color, i % 2 == 1);
+            assert!(analysis.loss >= 0.0);
+            assert_eq!(analysis.judgement, Judgement::from_loss(analysis.loss));
+        }
+
+        assert!((0.0..=100.0).contains(&report.black_accuracy));
+        assert!((0.0..=100.0).contains(&report.white_accuracy));
+        if let Some(ply) = report.turning_point {
+            assert!(report.moves[ply].loss > 0.0);
+        }
+
+        assert_eq!(
+            analyze_record("f5f5", 3, Variant::Standard).err(),
+            Some(OthelloError::Occupied)
+        );
+        assert_eq!(
+            analyze_record("z9", 3, Variant::Standard).err(),
+            Some(OthelloError::InvalidRecord)
+        );
+    }
+
+    #[test]
+    fn skips_are_left_out() {
+        // black skips after white's a3
+        let report = analyze_record("c4c3e6b4a4a5c2a3f4", 2, Variant::Standard).unwrap();
+        assert_eq!(report.moves.len(), 9);
+        assert_eq!(report.moves[8].ply, 9);
+        assert!(report.moves[8].color);
+    }
+}
@@ -0,0 +1,92 @@
+//! The reference client for the game server, see `wasm::server::client`.
+//! Run as `client <opponent> [black|white] [bot|human] [port]`. Without a
+//! bot, the moves are read from stdin, ex. "f5".
+
+use std::io::{self, BufRead};
+
+use wasm::{
+    bots, notation,
+    position::{AnyBoard, Position},
+    server::{ClientMessage, DEFAULT_PORT, client::Client},
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(opponent), Some(color)) = (
+        args.first(),
+        match args.get(1).map_or("black", String::as_str) {
+            "black" => Some(false),
+            "white" => Some(true),
+            _ => None,
+        },
+    ) else {
+        eprintln!("Usage: client <opponent> [black|white] [bot|human] [port]");
+        std::process::exit(2);
+    };
+    let port = match args.get(3).map(|port| port.parse()) {
+        None => DEFAULT_PORT,
+        Some(Ok(port)) => port,
+        Some(Err(err)) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let name = args.get(2).map_or("human", String::as_str);
+    let mut bot = match name {
+        "human" => None,
+        name => match bots::create_bot(name) {
+            Some(bot) => Some(bot),
+            None => {
+                eprintln!(
+                    "{}",
+                    wasm::error::OthelloError::UnknownBot(name.to_string())
+                );
+                std::process::exit(1);
+            }
+        },
+    };
+    let new_game = ClientMessage::NewGame {
+        name: name.to_string(),
+        opponent: opponent.clone(),
+        color,
+        size: None,
+        variant: Default::default(),
+    };
+
+    let result = Client::connect(("127.0.0.1", port)).and_then(|mut client| match &mut bot {
+        Some(bot) => client.play_bot(&new_game, bot.as_mut()),
+        None => client.play_game(&new_game, ask_human),
+    });
+    match result {
+        Ok(record) => println!(
+            "{:?}, black {} - white {}{}",
+            record.result,
+            record.black_count,
+            record.white_count,
+            record
+                .forfeit
+                .map_or(String::new(), |reason| format!(" ({reason})"))
+        ),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Shows the board and reads moves from stdin until one is valid
+fn ask_human(board: &AnyBoard, color: bool) -> (i8, i8) {
+    println!("{board:?}");
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        println!("Your move ({}):", if color { "O" } else { "X" });
+        let Some(Ok(line)) = lines.next() else {
+            std::process::exit(1);
+        };
+        match notation::parse_move_sized(line.trim(), board.size()) {
+            Some((x, y)) if board.is_valid_move(x, y, color) => return (x, y),
+            _ => println!("That isn't a valid move"),
+        }
+    }
+}
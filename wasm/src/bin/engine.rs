@@ -0,0 +1,56 @@
+//! An engine for `wasm::bots::external_bot`, answering with the moves of a
+//! built-in bot. Run as `engine [bot name]`; the bot is
+//! `deep_heuristic_bot` unless another is named.
+
+use std::{
+    io::{self, BufRead, Write},
+    time::Duration,
+};
+
+use wasm::{bots, error::OthelloError, notation, position::Position, variant::Variant};
+
+/// Share of the time limit the bot searches for, leaving the rest for
+/// reading the board and sending the answer back through the pipes
+const SEARCH_SHARE: f64 = 0.85;
+
+fn main() -> io::Result<()> {
+    let name = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "deep_heuristic_bot".to_string());
+    let Some(mut bot) = bots::create_bot(&name) else {
+        eprintln!("{}", OthelloError::UnknownBot(name));
+        std::process::exit(1);
+    };
+
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["variant", "standard"] => bot.set_variant(Variant::Standard),
+            ["variant", "antiOthello"] => bot.set_variant(Variant::AntiOthello),
+            ["time", ms] => bot.set_time_limit(
+                ms.parse()
+                    .ok()
+                    .map(|ms| Duration::from_millis(ms).mul_f64(SEARCH_SHARE)),
+            ),
+            ["move", board, color] => {
+                let Some(board) = notation::parse_any_board(board) else {
+                    eprintln!("{}", OthelloError::InvalidBoard);
+                    continue;
+                };
+                let color = color == "O";
+                let answer = if board.has_valid_move(color) {
+                    notation::format_move(board.ask_bot(bot.as_mut(), color))
+                } else {
+                    "pass".to_string()
+                };
+                writeln!(stdout, "{answer}")?;
+                stdout.flush()?;
+            }
+            ["quit"] => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+//! A text protocol engine for scripts and test harnesses, see `wasm::gtp`.
+//! Run as `gtp [bot name]`; the bot is `deep_heuristic_bot` unless another
+//! is named.
+
+use std::io::{self, BufRead, Write};
+
+use wasm::{bots, gtp::Engine};
+
+fn main() -> io::Result<()> {
+    let name = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "deep_heuristic_bot".to_string());
+    let Some(bot) = bots::create_bot(&name) else {
+        eprintln!("{}", wasm::error::OthelloError::UnknownBot(name));
+        std::process::exit(1);
+    };
+    let mut engine = Engine::new(bot);
+
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        if !engine.handle(&line?, &mut stdout)? {
+            break;
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! An NBoard engine, for desktop Othello GUIs. Run as `nboard [bot name]`;
+//! the bot is `deep_heuristic_bot` unless another is named.
+
+use std::io::{self, BufRead, Write};
+
+use wasm::nboard::Engine;
+
+fn main() -> io::Result<()> {
+    let bot = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "deep_heuristic_bot".to_string());
+    let mut engine = match Engine::new(&bot) {
+        Ok(engine) => engine,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        if !engine.handle(&line?, &mut stdout)? {
+            break;
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! The game server for local network play, see `wasm::server`. Run as
+//! `server [port] [time limit in ms]`. Each finished game is written to
+//! stdout as a line of JSON.
+
+use std::time::Duration;
+
+use wasm::server::{DEFAULT_PORT, DEFAULT_TIME_LIMIT, Server};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let port = args.next().map_or(Ok(DEFAULT_PORT), |port| port.parse());
+    let time_limit = args.next().map_or(Ok(DEFAULT_TIME_LIMIT), |ms| {
+        ms.parse().map(Duration::from_millis)
+    });
+    let (Ok(port), Ok(time_limit)) = (port, time_limit) else {
+        eprintln!("Usage: server [port] [time limit in ms]");
+        std::process::exit(2);
+    };
+
+    let result = Server::bind(("127.0.0.1", port), time_limit).and_then(|server| {
+        eprintln!("Listening on {}", server.local_addr()?);
+        server.serve(|record| {
+            if let Ok(json) = serde_json::to_string(record) {
+                println!("{json}");
+            }
+        })
+    });
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
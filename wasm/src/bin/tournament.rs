@@ -0,0 +1,50 @@
+//! Measures the strength of bots in a round robin, see `wasm::tournament`.
+//! Run as `tournament <rounds> <seed> [bot names]`; every built-in bot
+//! plays unless bots are named. Prints the arguments to rerun it with, then
+//! the Elo ratings, with the random bot at 0, and each bot's points per
+//! game.
+
+use wasm::{bots::BOT_NAMES, tournament};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let rounds = args.next().map(|rounds| rounds.parse::<u32>());
+    let seed = args.next().map(|seed| seed.parse::<u64>());
+    let (Some(Ok(rounds)), Some(Ok(seed))) = (rounds, seed) else {
+        eprintln!("Usage: tournament <rounds> <seed> [bot names]");
+        std::process::exit(2);
+    };
+    let named: Vec<String> = args.collect();
+    let names: Vec<&str> = if named.is_empty() {
+        BOT_NAMES.to_vec()
+    } else {
+        named.iter().map(String::as_str).collect()
+    };
+
+    let standings = tournament::round_robin(&names, rounds, seed, |round, _| {
+        eprintln!("Finished round {round} of {rounds}");
+    })
+    .unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    let elos = standings.elos();
+    let scores = standings.scores();
+    let mut rows: Vec<usize> = (0..names.len()).collect();
+    rows.sort_by(|&a, &b| elos[b].total_cmp(&elos[a]));
+
+    println!("# tournament {rounds} {seed} {}", names.join(" "));
+    println!(
+        "# {} rounds, {} games per pair, seed {}",
+        rounds, standings.games_per_pair, seed
+    );
+    for i in rows {
+        println!(
+            "{:<28} {:>6.0} {:>5.1}%",
+            names[i],
+            elos[i],
+            scores[i] * 100f32
+        );
+    }
+}
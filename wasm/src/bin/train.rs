@@ -0,0 +1,69 @@
+//! Learns weights for `wasm::bots::td_bot` by self-play, see
+//! `wasm::td_learning`. Run as `train <weights file> [games] [games between
+//! matches]`. Learning carries on from the weights in the file if it
+//! exists. After every few games, the weights are saved and a `td_bot`
+//! using them plays a match against `deep_heuristic_bot` to show how far
+//! learning has got.
+
+use std::path::Path;
+
+use wasm::{
+    bots::td_bot,
+    td_learning::{self, Trainer, Weights},
+};
+
+const OPPONENT: &str = "deep_heuristic_bot";
+/// Pairs of games in each match, one with each color
+const MATCH_PAIRS: u32 = 10;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next();
+    let games = args.next().map_or(Ok(10_000), |games| games.parse());
+    let match_every = args.next().map_or(Ok(1_000), |games| games.parse());
+    let (Some(path), Ok(games), Ok(match_every)) = (path, games, match_every) else {
+        eprintln!("Usage: train <weights file> [games] [games between matches]");
+        std::process::exit(2);
+    };
+    let match_every: u64 = match_every.max(1);
+
+    let weights = if Path::new(&path).exists() {
+        Weights::load(&path).unwrap_or_else(|err| {
+            eprintln!("{path}: {err}");
+            std::process::exit(1);
+        })
+    } else {
+        Weights::new()
+    };
+    let mut trainer = Trainer::new(weights, td_learning::Config::default());
+
+    for game in 1..=games {
+        if let Err(err) = trainer.play_game() {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        if game % match_every != 0 && game != games {
+            continue;
+        }
+
+        if let Err(err) = trainer.weights.save(&path) {
+            eprintln!("{path}: {err}");
+            std::process::exit(1);
+        }
+        let result = td_learning::play_match(
+            &trainer.weights,
+            td_bot::Config::default(),
+            OPPONENT,
+            MATCH_PAIRS,
+        )
+        .expect("the opponent is a built-in bot");
+        println!(
+            "{} games: {} wins, {} losses, {} draws against {OPPONENT} ({:.0}%)",
+            trainer.weights.games,
+            result.wins,
+            result.losses,
+            result.draws,
+            result.score() * 100f32,
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use std::fmt::Debug;
 
-#[derive(Clone)]
+use crate::{error::OthelloError, symmetry::Symmetry};
+
+#[cfg(test)]
+mod fuzz_tests;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Board {
     pub filled: u64,
     pub color: u64,
+    /// Squares where no piece can be placed, ex. the holes of a "holes"
+    /// variant. They are never filled, so they also break capturing lines.
+    pub blocked: u64,
 }
 
 static DIRECTIONS: &[(i8, i8)] = &[
@@ -22,10 +30,13 @@ impl Board {
         Board {
             filled: 0b00000000_00000000_00000000_00011000_00011000_00000000_00000000_00000000,
             color: 0b00000000_00000000_00000000_00010000_00001000_00000000_00000000_00000000,
+            blocked: 0,
         }
     }
 
+    /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
     pub fn set(&mut self, x: i8, y: i8, color: bool) {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
         let bit = 1u64 << (y * 8 + x);
         self.filled |= bit;
 
@@ -36,11 +47,39 @@ impl Board {
         }
     }
 
+    /// Blocks an empty square for the rest of the game
+    pub fn set_blocked(&mut self, x: i8, y: i8) {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        self.blocked |= 1u64 << (y * 8 + x);
+    }
+
     /// This method is used for testing only
     #[allow(dead_code)]
     pub fn clear(&mut self, x: i8, y: i8) {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
         let bit = 1u64 << (y * 8 + x);
         self.filled &= !bit;
+        self.color &= !bit;
+    }
+
+    /// Places a chip like `try_place_chip`, checking the coordinates first
+    /// and returning why the move failed.
+    ///
+    /// Colors: true = white; false = black
+    pub fn place_chip(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        if !Board::is_coord_valid(x, y) {
+            return Err(OthelloError::OutOfBounds);
+        }
+        if self.is_occupied(x, y) {
+            return Err(OthelloError::Occupied);
+        }
+        if self.is_blocked(x, y) {
+            return Err(OthelloError::Blocked);
+        }
+        if !self.try_place_chip(x, y, color) {
+            return Err(OthelloError::NoFlips);
+        }
+        Ok(())
     }
 
     /// Tries to place a chip and calculate results of action.
@@ -51,7 +90,8 @@ impl Board {
     ///
     /// Returns true if placement was successful.
     pub fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
-        if self.is_occupied(x, y) {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        if self.is_occupied(x, y) || self.is_blocked(x, y) {
             return false;
         }
 
@@ -105,6 +145,19 @@ impl Board {
         can_set
     }
 
+    /// The pieces a move would flip, as a bitboard. 0 if the move is not valid.
+    ///
+    /// Colors: true = white; false = black
+    ///
+    /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
+    pub fn flipped_by(&self, x: i8, y: i8, color: bool) -> u64 {
+        let mut future = self.clone();
+        if !future.try_place_chip(x, y, color) {
+            return 0;
+        }
+        (self.color ^ future.color) & self.filled
+    }
+
     /// Determines if a move is valid. This is a dry-run version of
     /// `try_place_chip``.
     ///
@@ -112,7 +165,8 @@ impl Board {
     ///
     /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
     pub fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
-        if self.is_occupied(x, y) {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        if self.is_occupied(x, y) || self.is_blocked(x, y) {
             return false;
         }
 
@@ -161,6 +215,13 @@ impl Board {
         valid_moves
     }
 
+    /// All valid moves for the specified color as a bitboard, bit y * 8 + x
+    pub fn valid_moves_mask(&self, color: bool) -> u64 {
+        self.get_all_valid_moves(color)
+            .into_iter()
+            .fold(0, |mask, (x, y)| mask | 1 << (y * 8 + x))
+    }
+
     /// Checks if a player has any valid move
     pub fn has_valid_move(&self, color: bool) -> bool {
         for y in 0..8 {
@@ -173,22 +234,124 @@ impl Board {
         false
     }
 
+    /// Determines who plays after `color` has moved. A player with no valid
+    /// moves skips, and the game ends when neither player can move.
+    ///
+    /// Returns None if the game is over.
+    pub fn next_turn(&self, color: bool) -> Option<bool> {
+        if self.has_valid_move(!color) {
+            Some(!color)
+        } else if self.has_valid_move(color) {
+            Some(color)
+        } else {
+            None
+        }
+    }
+
+    /// Counts the positions reached after exactly `depth` plies, with `color`
+    /// to move first. Skips count as a ply, and games that end before
+    /// `depth` count as a single position.
+    ///
+    /// Used to check move generation against known counts.
+    pub fn perft(&self, color: bool, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.get_all_valid_moves(color);
+
+        if moves.is_empty() {
+            if !self.has_valid_move(!color) {
+                // game over
+                return 1;
+            }
+            return self.perft(!color, depth - 1);
+        }
+
+        moves
+            .into_iter()
+            .map(|m| {
+                let mut future = self.clone();
+                future.try_place_chip(m.0, m.1, color);
+                future.perft(!color, depth - 1)
+            })
+            .sum()
+    }
+
     /// Counts the number of placed pieces on the board for a color
     pub fn count_pieces(&self, color: bool) -> u16 {
         let curr = self.filled & (if color { self.color } else { !self.color });
         curr.count_ones() as u16
     }
 
+    /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
     pub fn is_occupied(&self, x: i8, y: i8) -> bool {
-        return ((self.filled >> (y * 8 + x)) & 1) != 0;
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        ((self.filled >> (y * 8 + x)) & 1) != 0
     }
 
+    /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
     pub fn get_color(&self, x: i8, y: i8) -> bool {
-        return ((self.color >> (y * 8 + x)) & 1) != 0;
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        ((self.color >> (y * 8 + x)) & 1) != 0
     }
 
-    fn is_coord_valid(x: i8, y: i8) -> bool {
-        x >= 0 && x < 8 && y >= 0 && y < 8
+    /// Precondition: 0 <= x <= 7 and 0 <= y <= 7
+    pub fn is_blocked(&self, x: i8, y: i8) -> bool {
+        debug_assert!(Board::is_coord_valid(x, y), "({x}, {y}) is off the board");
+        ((self.blocked >> (y * 8 + x)) & 1) != 0
+    }
+
+    /// Rotates the board 90 degrees clockwise
+    pub fn rotate_90(&self) -> Board {
+        self.transform(Symmetry::Rotate90)
+    }
+
+    /// Mirrors the board left to right
+    pub fn flip_horizontal(&self) -> Board {
+        self.transform(Symmetry::FlipHorizontal)
+    }
+
+    /// Mirrors the board top to bottom
+    pub fn flip_vertical(&self) -> Board {
+        self.transform(Symmetry::FlipVertical)
+    }
+
+    /// Mirrors the board along the diagonal from (0, 0) to (7, 7)
+    pub fn flip_diagonal(&self) -> Board {
+        self.transform(Symmetry::FlipDiagonal)
+    }
+
+    /// Applies a rotation or reflection to the board.
+    /// Use `Symmetry::transform_move` to find where a move ends up.
+    pub fn transform(&self, symmetry: Symmetry) -> Board {
+        Board {
+            filled: symmetry.transform_bits(self.filled),
+            color: symmetry.transform_bits(self.color),
+            blocked: symmetry.transform_bits(self.blocked),
+        }
+    }
+
+    /// Finds the canonical form of the board: the smallest of its eight
+    /// rotations and reflections, comparing `filled`, then `color`, then
+    /// `blocked`.
+    /// Boards that are rotations or reflections of each other have the same
+    /// canonical form.
+    ///
+    /// Returns the canonical board and the symmetry that transforms this
+    /// board into it. If the board is symmetric, more than one symmetry
+    /// gives the canonical board; the first in `Symmetry::ALL` is returned.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .map(|symmetry| (self.transform(symmetry), symmetry))
+            .min_by_key(|(board, _)| (board.filled, board.color, board.blocked))
+            .expect("there are 8 symmetries")
+    }
+
+    /// Whether (x, y) is a square on the board
+    pub fn is_coord_valid(x: i8, y: i8) -> bool {
+        (0..8).contains(&x) && (0..8).contains(&y)
     }
 }
 
@@ -202,6 +365,8 @@ impl Debug for Board {
             for x in 0..8 {
                 res.push(if self.is_occupied(x, y) {
                     if self.get_color(x, y) { 'O' } else { 'X' }
+                } else if self.is_blocked(x, y) {
+                    '#'
                 } else {
                     '_'
                 });
@@ -219,6 +384,106 @@ impl Debug for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::create_board;
+
+    #[test]
+    fn place_chip_errors() {
+        let mut board = Board::new();
+        assert_eq!(
+            board.place_chip(8, 0, false),
+            Err(OthelloError::OutOfBounds)
+        );
+        assert_eq!(
+            board.place_chip(0, -1, false),
+            Err(OthelloError::OutOfBounds)
+        );
+        assert_eq!(board.place_chip(3, 3, false), Err(OthelloError::Occupied));
+        assert_eq!(board.place_chip(0, 0, false), Err(OthelloError::NoFlips));
+        assert_eq!(board, Board::new());
+
+        assert_eq!(board.place_chip(5, 4, false), Ok(()));
+        assert_eq!(board.count_pieces(false), 4);
+    }
+
+    #[test]
+    fn off_board_coordinates() {
+        // (8, 0) would wrap around to (0, 1) and (-1, 3) to (7, 2)
+        let mut board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ O X _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+        let before = board.clone();
+        assert_eq!(board.place_chip(8, 0, true), Err(OthelloError::OutOfBounds));
+        assert_eq!(
+            board.place_chip(-1, 3, true),
+            Err(OthelloError::OutOfBounds)
+        );
+        assert_eq!(board, before);
+        assert!(!Board::is_coord_valid(8, 0));
+        assert!(!Board::is_coord_valid(-1, 3));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "(8, 0) is off the board")]
+    fn unchecked_accessors_assert() {
+        Board::new().is_occupied(8, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "(-1, 3) is off the board")]
+    fn unchecked_placement_asserts() {
+        Board::new().try_place_chip(-1, 3, false);
+    }
+
+    #[test]
+    fn flipped_by() {
+        let board = Board::new();
+        // f5 flips e5
+        assert_eq!(board.flipped_by(5, 4, false), 1 << (4 * 8 + 4));
+        assert_eq!(board.flipped_by(0, 0, false), 0);
+        assert_eq!(board.flipped_by(3, 3, false), 0);
+    }
+
+    #[test]
+    fn valid_moves_mask() {
+        let board = Board::new();
+        let mask = board.valid_moves_mask(false);
+        assert_eq!(mask.count_ones(), 4);
+        for (x, y) in board.get_all_valid_moves(false) {
+            assert_ne!(mask & 1 << (y * 8 + x), 0);
+        }
+    }
+
+    #[test]
+    fn blocked_squares() {
+        let mut board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ O X _ _ _],
+            [_ _ # X O _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+        assert!(board.is_blocked(2, 4) && !board.is_occupied(2, 4));
+        assert!(!board.get_all_valid_moves(true).contains(&(2, 4)));
+        assert_eq!(board.place_chip(2, 4, true), Err(OthelloError::Blocked));
+
+        // a blocked square breaks the line like an empty square
+        board.set(1, 4, true);
+        assert!(!board.is_valid_move(0, 4, false));
+        assert_eq!(board.get_all_valid_moves(false).len(), 4);
+        assert_eq!(board.rotate_90().blocked.count_ones(), 1);
+    }
 
     #[test]
     fn set_and_clear() {
@@ -242,4 +507,160 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn transforms() {
+        let board = create_board!(
+            [X _ _ _ _ _ _ _],
+            [_ O _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ O],
+        );
+
+        let rotated = board.rotate_90();
+        assert!(rotated.is_occupied(7, 0) && !rotated.get_color(7, 0));
+        assert!(rotated.is_occupied(6, 1) && rotated.get_color(6, 1));
+        assert!(rotated.is_occupied(0, 7) && rotated.get_color(0, 7));
+
+        let flipped = board.flip_horizontal();
+        assert!(flipped.is_occupied(7, 0) && !flipped.get_color(7, 0));
+        assert!(flipped.is_occupied(0, 7) && flipped.get_color(0, 7));
+
+        let flipped = board.flip_vertical();
+        assert!(flipped.is_occupied(0, 7) && !flipped.get_color(0, 7));
+        assert!(flipped.is_occupied(1, 6) && flipped.get_color(1, 6));
+
+        assert_eq!(board.flip_diagonal(), board);
+        assert_eq!(board.rotate_90().rotate_90().rotate_90().rotate_90(), board);
+    }
+
+    #[test]
+    fn transformed_moves_stay_valid() {
+        let board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ X _ _ _ _ _],
+            [_ _ _ X O _ _ _],
+            [_ _ _ X X O _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        for symmetry in Symmetry::ALL {
+            let transformed = board.transform(symmetry);
+
+            for color in [false, true] {
+                let mut expected: Vec<(i8, i8)> = board
+                    .get_all_valid_moves(color)
+                    .into_iter()
+                    .map(|m| symmetry.transform_move(m))
+                    .collect();
+                expected.sort();
+
+                let mut actual = transformed.get_all_valid_moves(color);
+                actual.sort();
+
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_form() {
+        let board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ O X _ _ _],
+            [_ _ _ X X X _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+        let (canonical, _) = board.canonical();
+
+        for symmetry in Symmetry::ALL {
+            let (other, to_canonical) = board.transform(symmetry).canonical();
+            assert_eq!(other, canonical);
+            assert_eq!(board.transform(symmetry).transform(to_canonical), canonical);
+        }
+
+        // the starting board is symmetric, so it can be its own canonical form
+        let (canonical, _) = Board::new().canonical();
+        assert_eq!(Board::new().transform(Symmetry::FlipDiagonal), Board::new());
+        assert_eq!(canonical.canonical().0, canonical);
+    }
+
+    /// Known leaf counts from the starting position, indexed by depth
+    const STARTING_PERFT: &[u64] = &[1, 4, 12, 56, 244, 1396, 8200, 55092, 390216];
+
+    #[test]
+    fn perft_starting_position() {
+        let board = Board::new();
+        for (depth, expected) in STARTING_PERFT.iter().enumerate() {
+            assert_eq!(board.perft(false, depth as u32), *expected, "depth {depth}");
+        }
+    }
+
+    #[test]
+    fn perft_symmetric_positions() {
+        // every rotation and reflection of a position has the same counts
+        for symmetry in Symmetry::ALL {
+            let board = Board::new().transform(symmetry);
+            assert_eq!(board.perft(false, 5), STARTING_PERFT[5]);
+        }
+
+        // all four opening moves are symmetric, so each leads to a quarter of the positions
+        let mut board = Board::new();
+        board.try_place_chip(5, 4, false);
+        for depth in 0..6 {
+            assert_eq!(
+                board.perft(true, depth),
+                STARTING_PERFT[depth as usize + 1] / 4
+            );
+        }
+    }
+
+    #[test]
+    fn perft_skips_and_game_end() {
+        // black has no valid moves and must skip; white then has two moves,
+        // each of which takes black's last piece and ends the game
+        let board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ X O O O O],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+        );
+
+        assert_eq!(board.perft(false, 1), 1);
+        assert_eq!(board.perft(false, 2), 2);
+        assert_eq!(board.perft(false, 3), 2);
+        assert_eq!(board.perft(false, 10), 2);
+        assert_eq!(board.perft(true, 1), 2);
+
+        // neither player can move
+        let board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O O O O O],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+        );
+
+        assert_eq!(board.perft(false, 0), 1);
+        assert_eq!(board.perft(false, 5), 1);
+        assert_eq!(board.perft(true, 5), 1);
+    }
 }
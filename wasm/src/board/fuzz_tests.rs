@@ -0,0 +1,294 @@
+//! Randomized tests of the board rules.
+//!
+//! Random legal games are played while checking invariants after every
+//! move, and the `Board` is compared move by move against a simple
+//! reference implementation. Another implementation can be compared by
+//! implementing `Rules` for it and calling `compare_implementations`.
+
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::board::Board;
+
+/// The rules a board implementation is compared on
+trait Rules: Clone {
+    fn starting() -> Self;
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool;
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool;
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)>;
+    fn has_valid_move(&self, color: bool) -> bool;
+    fn count_pieces(&self, color: bool) -> u16;
+    /// The color of the piece on a square, None if empty
+    fn piece(&self, x: i8, y: i8) -> Option<bool>;
+}
+
+impl Rules for Board {
+    fn starting() -> Self {
+        Board::new()
+    }
+
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
+        Board::try_place_chip(self, x, y, color)
+    }
+
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
+        Board::is_valid_move(self, x, y, color)
+    }
+
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)> {
+        Board::get_all_valid_moves(self, color)
+    }
+
+    fn has_valid_move(&self, color: bool) -> bool {
+        Board::has_valid_move(self, color)
+    }
+
+    fn count_pieces(&self, color: bool) -> u16 {
+        Board::count_pieces(self, color)
+    }
+
+    fn piece(&self, x: i8, y: i8) -> Option<bool> {
+        if self.is_occupied(x, y) {
+            Some(self.get_color(x, y))
+        } else {
+            None
+        }
+    }
+}
+
+/// A slow, direct implementation of the rules to compare against
+#[derive(Clone)]
+struct ReferenceBoard {
+    cells: [[Option<bool>; 8]; 8],
+}
+
+impl ReferenceBoard {
+    /// Squares that would be flipped by a move, empty if the move is not valid
+    fn flips(&self, x: i8, y: i8, color: bool) -> Vec<(i8, i8)> {
+        if self.piece(x, y).is_some() {
+            return Vec::new();
+        }
+
+        let mut flips = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+
+                let mut line = Vec::new();
+                let (mut cx, mut cy) = (x + dx, y + dy);
+                while (0..8).contains(&cx) && (0..8).contains(&cy) {
+                    match self.piece(cx, cy) {
+                        Some(c) if c != color => line.push((cx, cy)),
+                        Some(_) => {
+                            flips.append(&mut line);
+                            break;
+                        }
+                        None => break,
+                    }
+                    cx += dx;
+                    cy += dy;
+                }
+            }
+        }
+
+        flips
+    }
+}
+
+impl Rules for ReferenceBoard {
+    fn starting() -> Self {
+        let mut cells = [[None; 8]; 8];
+        cells[3][3] = Some(true);
+        cells[4][4] = Some(true);
+        cells[3][4] = Some(false);
+        cells[4][3] = Some(false);
+        ReferenceBoard { cells }
+    }
+
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
+        let flips = self.flips(x, y, color);
+        if flips.is_empty() {
+            return false;
+        }
+
+        for (fx, fy) in flips {
+            self.cells[fy as usize][fx as usize] = Some(color);
+        }
+        self.cells[y as usize][x as usize] = Some(color);
+        true
+    }
+
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
+        !self.flips(x, y, color).is_empty()
+    }
+
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)> {
+        let mut moves = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                if self.is_valid_move(x, y, color) {
+                    moves.push((x, y));
+                }
+            }
+        }
+        moves
+    }
+
+    fn has_valid_move(&self, color: bool) -> bool {
+        !self.get_all_valid_moves(color).is_empty()
+    }
+
+    fn count_pieces(&self, color: bool) -> u16 {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|c| **c == Some(color))
+            .count() as u16
+    }
+
+    fn piece(&self, x: i8, y: i8) -> Option<bool> {
+        self.cells[y as usize][x as usize]
+    }
+}
+
+const GAMES: u64 = 100;
+
+/// Plays random legal games from the start, calling `check` before each
+/// move with the board, the color to move and the chosen move.
+fn play_random_games<B: Rules>(seed: u64, mut check: impl FnMut(&B, bool, (i8, i8))) {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..GAMES {
+        let mut board = B::starting();
+        let mut color = false;
+
+        loop {
+            let moves = board.get_all_valid_moves(color);
+            let m = match moves.choose(&mut rng) {
+                Some(m) => *m,
+                None => {
+                    if !board.has_valid_move(!color) {
+                        break;
+                    }
+                    color = !color;
+                    continue;
+                }
+            };
+
+            check(&board, color, m);
+            assert!(board.try_place_chip(m.0, m.1, color));
+            color = !color;
+        }
+    }
+}
+
+fn check_invariants<B: Rules>() {
+    play_random_games::<B>(0, |board, color, m| {
+        let valid_moves = board.get_all_valid_moves(color);
+        assert_eq!(board.has_valid_move(color), !valid_moves.is_empty());
+        assert_eq!(
+            board.has_valid_move(!color),
+            !board.get_all_valid_moves(!color).is_empty()
+        );
+
+        // is_valid_move agrees with the move list and with try_place_chip
+        for y in 0..8 {
+            for x in 0..8 {
+                let is_valid = board.is_valid_move(x, y, color);
+                assert_eq!(is_valid, valid_moves.contains(&(x, y)));
+
+                // failed placements leave the board unchanged
+                let mut future = board.clone();
+                assert_eq!(future.try_place_chip(x, y, color), is_valid);
+                if !is_valid {
+                    assert_same_pieces(&future, board);
+                }
+            }
+        }
+
+        let mut future = board.clone();
+        assert!(future.try_place_chip(m.0, m.1, color));
+        check_move_result(board, &future, color, m);
+    });
+}
+
+/// Checks that only the placed piece was added and only opponent pieces flipped
+fn check_move_result<B: Rules>(before: &B, after: &B, color: bool, m: (i8, i8)) {
+    let mut flipped = 0;
+
+    for y in 0..8 {
+        for x in 0..8 {
+            let old = before.piece(x, y);
+            let new = after.piece(x, y);
+
+            if (x, y) == m {
+                assert_eq!(old, None);
+                assert_eq!(new, Some(color));
+            } else if old != new {
+                assert_eq!(old, Some(!color), "only opponent pieces flip");
+                assert_eq!(new, Some(color));
+                flipped += 1;
+            }
+        }
+    }
+
+    assert!(flipped > 0, "a valid move flips at least one piece");
+
+    let total_before = before.count_pieces(false) + before.count_pieces(true);
+    let total_after = after.count_pieces(false) + after.count_pieces(true);
+    assert_eq!(total_after, total_before + 1);
+    assert_eq!(
+        after.count_pieces(color),
+        before.count_pieces(color) + 1 + flipped
+    );
+    assert_eq!(
+        after.count_pieces(!color),
+        before.count_pieces(!color) - flipped
+    );
+}
+
+fn assert_same_pieces<A: Rules, B: Rules>(a: &A, b: &B) {
+    for y in 0..8 {
+        for x in 0..8 {
+            assert_eq!(a.piece(x, y), b.piece(x, y), "piece at ({x}, {y})");
+        }
+    }
+}
+
+/// Plays the same random games on two implementations and checks that they
+/// agree on every move
+fn compare_implementations<A: Rules, B: Rules>() {
+    let mut other = B::starting();
+
+    play_random_games::<A>(2, |board, color, m| {
+        // a new game has started
+        if board.count_pieces(false) + board.count_pieces(true) == 4 {
+            other = B::starting();
+        }
+
+        assert_same_pieces(board, &other);
+        for c in [false, true] {
+            assert_eq!(board.get_all_valid_moves(c), other.get_all_valid_moves(c));
+            assert_eq!(board.has_valid_move(c), other.has_valid_move(c));
+            assert_eq!(board.count_pieces(c), other.count_pieces(c));
+        }
+
+        assert!(other.try_place_chip(m.0, m.1, color));
+    });
+}
+
+#[test]
+fn board_invariants() {
+    check_invariants::<Board>();
+}
+
+#[test]
+fn reference_board_invariants() {
+    check_invariants::<ReferenceBoard>();
+}
+
+#[test]
+fn board_matches_reference() {
+    compare_implementations::<Board, ReferenceBoard>();
+}
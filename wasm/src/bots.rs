@@ -1,21 +1,90 @@
-use crate::board::Board;
+use std::time::Duration;
 
+use crate::{
+    board::Board,
+    clock::{self, Clock, TimeControl},
+    error::OthelloError,
+    game::Game,
+    position::Position,
+    search::SearchControl,
+    sized_board::SizedBoard,
+    variant::Variant,
+};
+
+pub mod book_bot;
 pub mod center_bot;
 pub mod deep_heuristic_bot;
 pub mod deep_negative_heuristic_bot;
 pub mod deep_score_bot;
 pub mod edge_bot;
 pub mod edge_exclusive_bot;
+#[cfg(feature = "native")]
+pub mod external_bot;
 pub mod first_valid_bot;
 pub mod last_valid_bot;
+pub mod level_bot;
+pub mod mcts_bot;
 pub mod minmax_score_bot;
 pub mod random_bot;
 pub mod shallow_score_bot;
+pub mod td_bot;
+
+/// Names of the bots that can be created with `create_bot`
+pub const BOT_NAMES: &[&str] = &[
+    "center_bot",
+    "deep_heuristic_bot",
+    "deep_negative_heuristic_bot",
+    "deep_score_bot",
+    "edge_bot",
+    "edge_exclusive_bot",
+    "first_valid_bot",
+    "last_valid_bot",
+    "level_1",
+    "level_2",
+    "level_3",
+    "level_4",
+    "level_5",
+    "level_6",
+    "level_7",
+    "level_8",
+    "level_9",
+    "level_10",
+    "mcts_bot",
+    "minmax_score_bot",
+    "random_bot",
+    "shallow_score_bot",
+];
 
-pub struct BotRunner {
-    pub board: Board,
+/// Creates a bot with its default settings by name, ex. "deep_heuristic_bot".
+/// Returns None if there is no bot with that name.
+pub fn create_bot(name: &str) -> Option<Box<dyn MakeMove>> {
+    let bot: Box<dyn MakeMove> = match name {
+        "center_bot" => Box::new(center_bot::Bot::new()),
+        "deep_heuristic_bot" => Box::new(deep_heuristic_bot::Bot::new()),
+        "deep_negative_heuristic_bot" => Box::new(deep_negative_heuristic_bot::Bot::new()),
+        "deep_score_bot" => Box::new(deep_score_bot::Bot::new()),
+        "edge_bot" => Box::new(edge_bot::Bot::new()),
+        "edge_exclusive_bot" => Box::new(edge_exclusive_bot::Bot::new()),
+        "first_valid_bot" => Box::new(first_valid_bot::Bot::new()),
+        "last_valid_bot" => Box::new(last_valid_bot::Bot::new()),
+        "mcts_bot" => Box::new(mcts_bot::Bot::new()),
+        "minmax_score_bot" => Box::new(minmax_score_bot::Bot::new()),
+        "random_bot" => Box::new(random_bot::Bot::new()),
+        "shallow_score_bot" => Box::new(shallow_score_bot::Bot::new()),
+        _ => {
+            let level = level_bot::parse_name(name)?;
+            Box::new(level_bot::Bot::new(level).ok()?)
+        }
+    };
+    Some(bot)
+}
+
+pub struct BotRunner<B: Position = Board> {
+    pub game: Game<B>,
     white_bot: Option<Box<dyn MakeMove>>,
     black_bot: Option<Box<dyn MakeMove>>,
+    /// The players' clocks, None for games without time controls
+    clock: Option<Clock>,
 }
 
 impl BotRunner {
@@ -23,77 +92,429 @@ impl BotRunner {
         black_bot: Option<Box<dyn MakeMove>>,
         white_bot: Option<Box<dyn MakeMove>>,
     ) -> BotRunner {
+        BotRunner::with_variant(black_bot, white_bot, Variant::Standard)
+    }
+
+    /// Starts a game of `variant`, telling the bots which variant they play
+    pub fn with_variant(
+        black_bot: Option<Box<dyn MakeMove>>,
+        white_bot: Option<Box<dyn MakeMove>>,
+        variant: Variant,
+    ) -> BotRunner {
+        BotRunner::from_start(black_bot, white_bot, Board::new(), false, variant)
+    }
+}
+
+impl<B: Position> BotRunner<B> {
+    /// Starts a game of `variant` from `start`, which can be a board of any
+    /// size, with `first` to play. The bots are told which variant they play.
+    pub fn from_start(
+        mut black_bot: Option<Box<dyn MakeMove>>,
+        mut white_bot: Option<Box<dyn MakeMove>>,
+        start: B,
+        first: bool,
+        variant: Variant,
+    ) -> BotRunner<B> {
+        for bot in [&mut black_bot, &mut white_bot].into_iter().flatten() {
+            bot.set_variant(variant);
+        }
+
         BotRunner {
             black_bot,
             white_bot,
-            board: Board::new(),
+            game: Game::from_start(start, first, variant),
+            clock: None,
         }
     }
 
-    pub fn run_game_to_end(&mut self) {
-        let (black_bot, white_bot) = match (&mut self.black_bot, &mut self.white_bot) {
-            (Some(black_bot), Some(white_bot)) => (black_bot, white_bot),
-            _ => return,
+    /// Plays the game with time controls, starting the clock of the player
+    /// to move, or without them for None. A player who runs out of time
+    /// loses.
+    pub fn set_time_control(&mut self, control: Option<TimeControl>) {
+        self.clock = control.map(|control| {
+            let mut clock = Clock::new(control);
+            clock.start(self.game.turn());
+            clock
+        });
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// How long the player to move should think, planned from the time left
+    /// on its clock. None without time controls or if the game is over.
+    pub fn planned_move_time(&self) -> Option<Duration> {
+        let clock = self.clock.as_ref()?;
+        let color = self.game.turn()?;
+        Some(clock::plan_move_time(
+            clock.control(),
+            clock.remaining(color),
+            self.moves_left(),
+        ))
+    }
+
+    /// Starts the game again from its starting position, with full clocks
+    pub fn restart(&mut self) {
+        self.game = self.game.restarted();
+        if let Some(clock) = &self.clock {
+            self.set_time_control(Some(clock.control()));
+        }
+    }
+
+    /// Plays a move for a human player, stopping their clock.
+    ///
+    /// Returns an error if it isn't `color`'s turn, the move isn't valid or
+    /// a player has run out of time.
+    pub fn play(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        self.check_clock()?;
+        self.game.play(x, y, color)?;
+        self.press_clock(color)
+    }
+
+    /// Plays the game until it ends. If a bot doesn't make a valid move or
+    /// runs out of time, it forfeits and the game stops with the bot's
+    /// error.
+    pub fn run_game_to_end(&mut self) -> Result<(), OthelloError> {
+        if self.black_bot.is_none() || self.white_bot.is_none() {
+            return Err(OthelloError::NotABot);
+        }
+
+        while let Some(color) = self.game.turn() {
+            self.run_bot(color)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the black bot's move once. Returns the move if it was successful.
+    /// Returns an error if black isn't a bot, it isn't black's turn or the
+    /// bot didn't make a valid move.
+    pub fn run_black_bot(&mut self) -> Result<(i8, i8), OthelloError> {
+        self.run_bot(false)
+    }
+
+    /// Runs the white bot's move once. Returns the move if it was successful.
+    /// Returns an error if white isn't a bot, it isn't white's turn or the
+    /// bot didn't make a valid move.
+    pub fn run_white_bot(&mut self) -> Result<(i8, i8), OthelloError> {
+        self.run_bot(true)
+    }
+
+    /// Takes back moves until it's a human player's turn, so that a human
+    /// playing a bot gets back to their last move. If both or neither
+    /// players are bots, one move is taken back.
+    ///
+    /// Returns false if there are no moves to take back.
+    pub fn undo(&mut self) -> bool {
+        if !self.game.undo() {
+            return false;
+        }
+
+        if self.has_human() {
+            while self.game.turn().is_some_and(|color| self.is_bot(color)) && self.game.undo() {}
+        }
+        self.restart_clock();
+        true
+    }
+
+    /// Plays moves that were taken back until it's a human player's turn.
+    /// If both or neither players are bots, one move is played again.
+    ///
+    /// Returns false if there are no moves to play again.
+    pub fn redo(&mut self) -> bool {
+        if !self.game.redo() {
+            return false;
+        }
+
+        if self.has_human() {
+            while self.game.turn().is_some_and(|color| self.is_bot(color)) && self.game.redo() {}
+        }
+        self.restart_clock();
+        true
+    }
+
+    /// Goes to the position after `ply` plies, see `Game::jump_to_ply`
+    pub fn jump_to_ply(&mut self, ply: usize) -> bool {
+        let jumped = self.game.jump_to_ply(ply);
+        self.restart_clock();
+        jumped
+    }
+
+    fn run_bot(&mut self, color: bool) -> Result<(i8, i8), OthelloError> {
+        self.check_clock()?;
+        let clock = self.clock.as_ref().map(|clock| {
+            let remaining = clock.remaining(color);
+            (remaining, clock.control(), self.moves_left())
+        });
+        let bot = if color {
+            self.white_bot.as_mut()
+        } else {
+            self.black_bot.as_mut()
         };
+        let bot = bot.ok_or(OthelloError::NotABot)?;
 
-        loop {
-            let mut moved = false;
+        match self.game.turn() {
+            None => return Err(OthelloError::GameOver),
+            Some(turn) if turn != color => return Err(OthelloError::WrongTurn),
+            Some(_) => {}
+        }
 
-            let black_move = black_bot.make_move(&self.board, false);
-            if black_move.0 >= 0
-                && black_move.1 >= 0
-                && self.board.try_place_chip(black_move.0, black_move.1, false)
-            {
-                moved = true;
-            }
+        if let Some((remaining, control, moves_left)) = clock {
+            bot.set_remaining_time(remaining, control, moves_left);
+        }
+        let m = self.game.board().ask_bot(bot.as_mut(), color);
+        if let Some(reason) = bot.forfeit_reason() {
+            return Err(OthelloError::Forfeit(reason));
+        }
+        self.game.play(m.0, m.1, color)?;
+        self.press_clock(color)?;
+        Ok(m)
+    }
 
-            let white_move = white_bot.make_move(&self.board, true);
-            if white_move.0 >= 0
-                && white_move.1 >= 0
-                && self.board.try_place_chip(white_move.0, white_move.1, true)
-            {
-                moved = true;
-            }
+    /// About how many more moves the player to move will make: half of the
+    /// empty squares
+    fn moves_left(&self) -> u32 {
+        let board = self.game.board();
+        let squares = (board.size() as u32).pow(2);
+        let pieces = board.count_pieces(true) + board.count_pieces(false);
+        (squares - pieces as u32).div_ceil(2)
+    }
 
-            if !moved {
-                break;
-            }
+    /// Returns an error if a player has run out of time
+    fn check_clock(&self) -> Result<(), OthelloError> {
+        match self.clock.as_ref().and_then(Clock::flagged) {
+            Some(color) => Err(OthelloError::OutOfTime(color)),
+            None => Ok(()),
         }
     }
 
-    /// Runs the black bot's move once. Returns true if move was successful.
-    /// If the move was unsuccessful, assume there are no valid moves and skip.
-    pub fn run_black_bot(&mut self) -> Option<(i8, i8)> {
-        if let Some(black_bot) = &mut self.black_bot {
-            let black_move = black_bot.make_move(&self.board, false);
-            if black_move.0 >= 0
-                && black_move.1 >= 0
-                && self.board.try_place_chip(black_move.0, black_move.1, false)
-            {
-                return Some(black_move);
-            }
+    /// Stops `color`'s clock after its move and starts the next player's.
+    /// The move is taken back if `color` ran out of time before making it.
+    fn press_clock(&mut self, color: bool) -> Result<(), OthelloError> {
+        let Some(clock) = &mut self.clock else {
+            return Ok(());
+        };
+
+        if let Err(err) = clock.press(color, self.game.turn()) {
+            self.game.undo();
+            return Err(err);
         }
+        Ok(())
+    }
 
-        None
+    /// Runs the clock of the player to move, after moving through the
+    /// history
+    fn restart_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.start(self.game.turn());
+        }
     }
 
-    /// Runs the white bot's move once. Returns true if move was successful.
-    /// If the move was unsuccessful, assume there are no valid moves and skip.
-    pub fn run_white_bot(&mut self) -> Option<(i8, i8)> {
-        if let Some(white_bot) = &mut self.white_bot {
-            let white_move = white_bot.make_move(&self.board, true);
-            if white_move.0 >= 0
-                && white_move.1 >= 0
-                && self.board.try_place_chip(white_move.0, white_move.1, true)
-            {
-                return Some(white_move);
-            }
+    fn is_bot(&self, color: bool) -> bool {
+        if color {
+            self.white_bot.is_some()
+        } else {
+            self.black_bot.is_some()
         }
+    }
 
-        None
+    /// True if exactly one of the players is a bot
+    fn has_human(&self) -> bool {
+        self.is_bot(false) != self.is_bot(true)
     }
 }
 
 pub trait MakeMove {
     fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8);
+
+    /// `make_move` for boards that aren't 8x8
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8);
+
+    /// Number of positions the bot looked at while choosing its last move.
+    /// None for bots that don't search.
+    fn nodes_searched(&self) -> Option<u64> {
+        None
+    }
+
+    /// Limits how long the bot thinks for each move. Bots that search stop
+    /// when time runs out and play the best move found so far. Other bots
+    /// ignore it.
+    fn set_time_limit(&mut self, _limit: Option<Duration>) {}
+
+    /// Lets `control` watch the bot's searches and stop them early. Bots
+    /// that don't search ignore it.
+    fn set_search_control(&mut self, _control: Option<Box<dyn SearchControl>>) {}
+
+    /// Tells the bot which rules it is playing by. Bots that don't look at
+    /// who is winning ignore it.
+    fn set_variant(&mut self, _variant: Variant) {}
+
+    /// Seeds the bot's random choices, so that it plays the same moves
+    /// from the same positions. Bots that make no random choices ignore it.
+    fn set_seed(&mut self, _seed: u64) {}
+
+    /// Tells the bot the time left on its clock before each move in a game
+    /// with time controls, with about how many moves it has left to make.
+    /// By default the bot plans its time for the move with
+    /// `clock::plan_move_time` and sets it as its time limit, so bots that
+    /// search stop in time.
+    fn set_remaining_time(&mut self, remaining: Duration, control: TimeControl, moves_left: u32) {
+        self.set_time_limit(Some(clock::plan_move_time(control, remaining, moves_left)));
+    }
+
+    /// Why the bot gave up the game, ex. because the engine it asks for
+    /// moves crashed. None for bots that always make a move.
+    fn forfeit_reason(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use crate::{
+        board::Board,
+        bots::{BOT_NAMES, BotRunner, MakeMove, create_bot, first_valid_bot, random_bot},
+        clock::TimeControl,
+        error::OthelloError,
+        position::Position,
+        sized_board::SizedBoard,
+        variant::Variant,
+    };
+
+    /// Plays the first valid move, remembering the variant it was told
+    struct VariantBot {
+        variant: Rc<Cell<Option<Variant>>>,
+    }
+
+    impl MakeMove for VariantBot {
+        fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+            board.get_all_valid_moves(color)[0]
+        }
+
+        fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+            board.get_all_valid_moves(color)[0]
+        }
+
+        fn set_variant(&mut self, variant: Variant) {
+            self.variant.set(Some(variant));
+        }
+    }
+
+    #[test]
+    fn all_bot_names_create_bots() {
+        for name in BOT_NAMES {
+            assert!(create_bot(name).is_some(), "{name}");
+        }
+        assert!(create_bot("no_such_bot").is_none());
+    }
+
+    #[test]
+    fn undo_against_bot_returns_to_human_turn() {
+        // human plays black against a bot
+        let mut runner = BotRunner::new(None, Some(Box::new(first_valid_bot::Bot::new())));
+
+        assert_eq!(runner.run_black_bot(), Err(OthelloError::NotABot));
+        runner.game.play(5, 4, false).unwrap();
+        assert!(runner.run_white_bot().is_ok());
+        assert_eq!(runner.game.history().len(), 2);
+
+        assert!(runner.undo());
+        assert_eq!(runner.game.history().len(), 0);
+        assert_eq!(runner.game.turn(), Some(false));
+
+        assert!(runner.redo());
+        assert_eq!(runner.game.history().len(), 2);
+        assert_eq!(runner.game.turn(), Some(false));
+    }
+
+    #[test]
+    fn run_game_to_end() {
+        let mut runner = BotRunner::new(
+            Some(Box::new(first_valid_bot::Bot::new())),
+            Some(Box::new(first_valid_bot::Bot::new())),
+        );
+        assert_eq!(runner.run_white_bot(), Err(OthelloError::WrongTurn));
+        assert_eq!(runner.run_game_to_end(), Ok(()));
+        assert_eq!(runner.game.turn(), None);
+        assert_eq!(runner.run_black_bot(), Err(OthelloError::GameOver));
+    }
+
+    #[test]
+    fn time_controls() {
+        // the bots finish their searches well inside this clock, however
+        // slowly the tests run
+        let total = Duration::from_secs(60);
+        let mut runner = BotRunner::new(create_bot("deep_heuristic_bot"), create_bot("mcts_bot"));
+        runner.set_time_control(Some(TimeControl::Total {
+            total,
+            increment: Duration::ZERO,
+        }));
+        assert_eq!(runner.clock().unwrap().running(), Some(false));
+        let planned = runner.planned_move_time().unwrap();
+        assert!(planned > Duration::ZERO && planned < total / 10);
+
+        assert_eq!(runner.run_game_to_end(), Ok(()));
+        let clock = runner.clock().unwrap();
+        assert_eq!((clock.running(), clock.flagged()), (None, None));
+        assert!(clock.remaining(false) > Duration::ZERO);
+
+        // a human who thinks too long loses on time
+        let mut runner = BotRunner::new(None, create_bot("random_bot"));
+        runner.set_time_control(Some(TimeControl::PerMove(Duration::from_millis(50))));
+        runner.play(5, 4, false).unwrap();
+        runner.run_white_bot().unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(
+            runner.play(5, 2, false),
+            Err(OthelloError::OutOfTime(false))
+        );
+        assert_eq!(runner.run_white_bot(), Err(OthelloError::OutOfTime(false)));
+        assert_eq!(runner.game.history().len(), 2);
+
+        runner.restart();
+        assert!(runner.game.history().is_empty());
+        assert_eq!(runner.clock().unwrap().flagged(), None);
+    }
+
+    #[test]
+    fn bots_play_other_sizes() {
+        for name in BOT_NAMES {
+            let mut runner = BotRunner::from_start(
+                create_bot(name),
+                create_bot("random_bot"),
+                SizedBoard::new(6).unwrap(),
+                false,
+                Variant::Standard,
+            );
+            assert_eq!(runner.run_game_to_end(), Ok(()), "{name}");
+        }
+
+        for size in [4, 10] {
+            let mut runner = BotRunner::from_start(
+                Some(Box::new(random_bot::Bot::new())),
+                Some(Box::new(first_valid_bot::Bot::new())),
+                SizedBoard::new(size).unwrap(),
+                false,
+                Variant::Standard,
+            );
+            assert_eq!(runner.run_game_to_end(), Ok(()));
+            assert_eq!(runner.game.board().size(), size as i8);
+        }
+    }
+
+    #[test]
+    fn bots_are_told_the_variant() {
+        let variant = Rc::new(Cell::new(None));
+        let runner = BotRunner::with_variant(
+            Some(Box::new(VariantBot {
+                variant: variant.clone(),
+            })),
+            None,
+            Variant::AntiOthello,
+        );
+        assert_eq!(variant.get(), Some(Variant::AntiOthello));
+        assert_eq!(runner.game.variant(), Variant::AntiOthello);
+    }
 }
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    board::Board, bots::MakeMove, opening_book::OpeningBook, search::SearchControl,
+    sized_board::SizedBoard, variant::Variant,
+};
+
+/// This bot plays moves from an opening book. Once the game leaves the book,
+/// another bot chooses the moves. Books are of standard 8x8 games, so only
+/// the other bot plays in other variants and on other board sizes.
+pub struct Bot {
+    book: OpeningBook,
+    fallback: Box<dyn MakeMove>,
+    config: Config,
+    rng: StdRng,
+    variant: Variant,
+    /// Whether the last move came from the book rather than the other bot
+    played_book_move: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Book moves played in fewer games than this are ignored
+    pub min_games: u32,
+    /// Picks book moves randomly, weighted by how often they were played and
+    /// how well they scored. Otherwise the best scoring move is always played.
+    pub weighted_random: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            min_games: 1,
+            weighted_random: true,
+        }
+    }
+}
+
+impl Bot {
+    pub fn new(book: OpeningBook, fallback: Box<dyn MakeMove>) -> Bot {
+        Bot::with_config(book, fallback, Config::default())
+    }
+
+    pub fn with_config(book: OpeningBook, fallback: Box<dyn MakeMove>, config: Config) -> Bot {
+        Bot {
+            book,
+            fallback,
+            config,
+            rng: StdRng::from_os_rng(),
+            variant: Variant::Standard,
+            played_book_move: false,
+        }
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.played_book_move = false;
+        // book positions have no blocked squares, so they can't tell
+        // boards with holes apart from boards without
+        if self.variant != Variant::Standard || board.blocked != 0 {
+            return self.fallback.make_move(board, color);
+        }
+
+        let book_move = self.book.choose_move(
+            board,
+            color,
+            self.config.min_games,
+            self.config.weighted_random,
+            &mut self.rng,
+        );
+
+        match book_move {
+            Some(m) => {
+                self.played_book_move = true;
+                m
+            }
+            None => self.fallback.make_move(board, color),
+        }
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.played_book_move = false;
+        self.fallback.make_move_sized(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.fallback.set_seed(seed);
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        if self.played_book_move {
+            return Some(0);
+        }
+        self.fallback.nodes_searched()
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.fallback.set_time_limit(limit);
+    }
+
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.fallback.set_search_control(control);
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        self.fallback.set_variant(variant);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{deep_heuristic_bot, first_valid_bot};
+
+    fn book_bot(fallback: Box<dyn MakeMove>) -> Bot {
+        let mut book = OpeningBook::new();
+        book.add_game_record("f5d6c3d3c4f4");
+        let config = Config {
+            min_games: 1,
+            weighted_random: false,
+        };
+        Bot::with_config(book, fallback, config)
+    }
+
+    /// The board after f5, where the book plays d6 and the first valid
+    /// move is f4
+    fn after_f5() -> Board {
+        let mut board = Board::new();
+        board.place_chip(5, 4, false).unwrap();
+        board
+    }
+
+    #[test]
+    fn plays_book_moves() {
+        let mut bot = book_bot(Box::new(deep_heuristic_bot::Bot::new()));
+        let m = bot.make_move(&Board::new(), false);
+        // the four first moves are the same move in the book
+        assert!([(5, 4), (4, 5), (3, 2), (2, 3)].contains(&m));
+        assert_eq!(bot.nodes_searched(), Some(0));
+
+        assert_eq!(bot.make_move(&after_f5(), true), (3, 5));
+        assert_eq!(bot.nodes_searched(), Some(0));
+    }
+
+    #[test]
+    fn falls_back_out_of_book() {
+        let mut bot = book_bot(Box::new(deep_heuristic_bot::Bot::new()));
+        let mut board = after_f5();
+        // f6 isn't in the book
+        board.place_chip(5, 5, true).unwrap();
+
+        let m = bot.make_move(&board, false);
+        assert!(board.is_valid_move(m.0, m.1, false));
+        assert!(bot.nodes_searched().unwrap() > 0);
+    }
+
+    #[test]
+    fn falls_back_in_anti_othello() {
+        let mut bot = book_bot(Box::new(first_valid_bot::Bot::new()));
+        bot.set_variant(Variant::AntiOthello);
+        assert_eq!(bot.make_move(&after_f5(), true), (5, 3));
+    }
+
+    #[test]
+    fn falls_back_with_blocked_squares() {
+        let mut bot = book_bot(Box::new(first_valid_bot::Bot::new()));
+        let mut board = after_f5();
+        board.set_blocked(0, 0);
+        assert_eq!(bot.make_move(&board, true), (5, 3));
+
+        assert_eq!(bot.make_move(&after_f5(), true), (3, 5));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{board::Board, bots::MakeMove};
+use crate::{board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard};
 
 /// This bot choses the move that is closest to an edge
 pub struct Bot {}
@@ -7,24 +7,30 @@ impl Bot {
     pub fn new() -> Bot {
         Bot {}
     }
-}
 
-impl MakeMove for Bot {
-    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
         let best_move = board
             .get_all_valid_moves(color)
             .into_iter()
-            .max_by_key(|m| evaluate_move(m));
+            .max_by_key(|m| evaluate_move(m, board.size()));
+
+        best_move.unwrap_or((-1, -1))
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
 
-        match best_move {
-            Some(x) => x,
-            None => (-1, -1),
-        }
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
     }
 }
 
-fn evaluate_move(m: &(i8, i8)) -> i8 {
-    let x_score = if m.0 < 4 { m.0 } else { 7 - m.0 };
-    let y_score = if m.1 < 4 { m.1 } else { 7 - m.1 };
+fn evaluate_move(m: &(i8, i8), size: i8) -> i8 {
+    let half = size / 2;
+    let x_score = if m.0 < half { m.0 } else { size - 1 - m.0 };
+    let y_score = if m.1 < half { m.1 } else { size - 1 - m.1 };
     x_score + y_score
 }
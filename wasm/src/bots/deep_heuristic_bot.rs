@@ -1,12 +1,72 @@
-use crate::{board::Board, bots::MakeMove};
+use std::time::Duration;
+
+use crate::{
+    board::Board,
+    bots::MakeMove,
+    position::Position,
+    search::{Search, SearchControl},
+    sized_board::SizedBoard,
+    variant::Variant,
+};
 
 /// This bot maximizes a heuristic score (guessed by the developer)
 /// by doing a 5-deep minmax search
-pub struct Bot {}
+pub struct Bot {
+    config: Config,
+    nodes: u64,
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Plies searched, including the bot's own move
+    pub depth: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { depth: 5 }
+    }
+}
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot {}
+        Bot::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Bot {
+        Bot {
+            config,
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let mut search = Search::new(self.time_limit, self.control.as_deref_mut());
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            return (-1, -1);
+        }
+
+        // searching one ply deeper at a time leaves a move to play when the
+        // search is stopped early
+        for depth in 1..=self.config.depth {
+            match search_moves(board, color, &moves, depth, self.variant, &mut search) {
+                Some(m) => search.finish_depth(depth, m),
+                None => break,
+            }
+            if search.stopped() {
+                break;
+            }
+        }
+
+        self.nodes = search.nodes;
+        search.best_move().unwrap_or(moves[0])
     }
 }
 
@@ -15,44 +75,104 @@ const POS_INF_SCORE: f32 = f32::MAX;
 
 impl MakeMove for Bot {
     fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
-        let moves = board.get_all_valid_moves(color).into_iter();
+        self.choose_move(board, color)
+    }
 
-        let moves = moves;
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
 
-        let mut best_score = NEG_INF_SCORE;
-        let mut best_move = (-1, -1);
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
 
-        for m in moves {
-            let mut future = board.clone();
-            future.try_place_chip(m.0, m.1, color);
-            let result = evaluate_board(&future, color, !color, 4, NEG_INF_SCORE, POS_INF_SCORE);
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
 
-            if result > best_score {
-                best_move = m;
-                best_score = result;
-            }
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+/// Finds the best of `moves` by searching `depth` plies deep.
+/// Returns None if the search was stopped before it finished.
+fn search_moves<B: Position>(
+    board: &B,
+    color: bool,
+    moves: &[(i8, i8)],
+    depth: u16,
+    variant: Variant,
+    search: &mut Search,
+) -> Option<(i8, i8)> {
+    let mut best_score = NEG_INF_SCORE;
+    let mut best_move = (-1, -1);
+
+    for &m in moves {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        let result = evaluate_board(
+            &future,
+            color,
+            !color,
+            depth - 1,
+            NEG_INF_SCORE,
+            POS_INF_SCORE,
+            variant,
+            search,
+        );
+        if search.stopped() {
+            return None;
         }
 
-        best_move
+        if result > best_score {
+            best_move = m;
+            best_score = result;
+        }
     }
+
+    Some(best_move)
 }
 
-pub fn evaluate_board(
-    board: &Board,
+/// Scores the board for `color` with a minmax search `depth` plies deep,
+/// using the heuristic score as it counts in `variant`
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_board<B: Position>(
+    board: &B,
     color: bool,
     next_turn_color: bool,
     depth: u16,
     alpha: f32,
     beta: f32,
+    variant: Variant,
+    search: &mut Search,
 ) -> f32 {
+    if search.visit() {
+        // out of time, the result is thrown away
+        return alpha;
+    }
+
     if depth == 0 {
-        return heuristic_score(board, color);
+        return heuristic_score(board, color, variant);
     }
 
     let next_moves = board.get_all_valid_moves(next_turn_color);
 
     if next_moves.is_empty() {
-        return evaluate_board(&board, color, !next_turn_color, depth - 1, alpha, beta);
+        return evaluate_board(
+            board,
+            color,
+            !next_turn_color,
+            depth - 1,
+            alpha,
+            beta,
+            variant,
+            search,
+        );
     }
 
     let next_moves = next_moves.into_iter();
@@ -65,8 +185,16 @@ pub fn evaluate_board(
             let mut future_board = board.clone();
             future_board.try_place_chip(m.0, m.1, next_turn_color);
             // this branch is (result or worse)
-            let result =
-                evaluate_board(&future_board, color, !next_turn_color, depth - 1, max, beta);
+            let result = evaluate_board(
+                &future_board,
+                color,
+                !next_turn_color,
+                depth - 1,
+                max,
+                beta,
+                variant,
+                search,
+            );
             // alphabeta = result.1;
 
             if result >= max {
@@ -95,6 +223,8 @@ pub fn evaluate_board(
                 depth - 1,
                 alpha,
                 min,
+                variant,
+                search,
             );
 
             if result < min {
@@ -111,10 +241,14 @@ pub fn evaluate_board(
     }
 }
 
-fn heuristic_score(board: &Board, color: bool) -> f32 {
-    let score = f32::from(board.count_pieces(color));
-    let opponent_score = f32::from(board.count_pieces(!color));
-    let remaining_percent = 1f32 - (score + opponent_score) / 64f32;
+/// The score for `color`: the discs that count for it in `variant`, with
+/// extra points for edges and corners. Corners and edges can't be taken
+/// back easily, so they are worth holding in both variants.
+pub fn heuristic_score<B: Position>(board: &B, color: bool, variant: Variant) -> f32 {
+    let last = board.size() - 1;
+    let score = f32::from(variant.disc_score(board, color));
+    let opponent_score = f32::from(variant.disc_score(board, !color));
+    let remaining_percent = 1f32 - (score + opponent_score) / f32::from(board.size()).powi(2);
 
     let mut edge_score = 0f32;
     let mut opponent_edge_score = 0f32;
@@ -124,7 +258,7 @@ fn heuristic_score(board: &Board, color: bool) -> f32 {
 
     // counts the number of edge pieces for the opponent
     // skips the edges
-    for i in 1..7 {
+    for i in 1..last {
         // top
         if board.is_occupied(i, 0) {
             if board.get_color(i, 0) == color {
@@ -135,8 +269,8 @@ fn heuristic_score(board: &Board, color: bool) -> f32 {
         }
 
         // bottom
-        if board.is_occupied(i, 7) {
-            if board.get_color(i, 7) == color {
+        if board.is_occupied(i, last) {
+            if board.get_color(i, last) == color {
                 edge_score += 1f32;
             } else {
                 opponent_edge_score += 1f32;
@@ -153,8 +287,8 @@ fn heuristic_score(board: &Board, color: bool) -> f32 {
         }
 
         // right
-        if board.is_occupied(7, i) {
-            if board.get_color(7, i) == color {
+        if board.is_occupied(last, i) {
+            if board.get_color(last, i) == color {
                 edge_score += 1f32;
             } else {
                 opponent_edge_score += 1f32;
@@ -171,24 +305,24 @@ fn heuristic_score(board: &Board, color: bool) -> f32 {
         }
     }
 
-    if board.is_occupied(7, 0) {
-        if board.get_color(7, 0) == color {
+    if board.is_occupied(last, 0) {
+        if board.get_color(last, 0) == color {
             corner_score += 1f32;
         } else {
             opponent_corner_score += 1f32;
         }
     }
 
-    if board.is_occupied(0, 7) {
-        if board.get_color(0, 7) == color {
+    if board.is_occupied(0, last) {
+        if board.get_color(0, last) == color {
             corner_score += 1f32;
         } else {
             opponent_corner_score += 1f32;
         }
     }
 
-    if board.is_occupied(7, 7) {
-        if board.get_color(7, 7) == color {
+    if board.is_occupied(last, last) {
+        if board.get_color(last, last) == color {
             corner_score += 1f32;
         } else {
             opponent_corner_score += 1f32;
@@ -200,3 +334,50 @@ fn heuristic_score(board: &Board, color: bool) -> f32 {
             + (corner_score - opponent_corner_score) * 10f32)
             * (1f32 + remaining_percent) // edges and corners are strong, but don't matter as much in the end game
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        board::Board,
+        bots::{MakeMove, deep_heuristic_bot::Bot},
+        search::{Progress, SearchControl},
+    };
+
+    /// Stops once a depth is finished, sharing what it saw with the test
+    struct StopAtDepth {
+        depth: u16,
+        seen: std::rc::Rc<std::cell::RefCell<Vec<Progress>>>,
+    }
+
+    impl SearchControl for StopAtDepth {
+        fn should_stop(&mut self, progress: &Progress) -> bool {
+            self.seen.borrow_mut().push(progress.clone());
+            progress.depth >= self.depth
+        }
+    }
+
+    #[test]
+    fn stops_with_best_move_so_far() {
+        let board = Board::new();
+        let seen = std::rc::Rc::default();
+
+        let mut bot = Bot::new();
+        bot.set_search_control(Some(Box::new(StopAtDepth {
+            depth: 2,
+            seen: std::rc::Rc::clone(&seen),
+        })));
+        let m = bot.make_move(&board, false);
+
+        let seen = seen.borrow();
+        let last = seen.last().unwrap();
+        assert_eq!(last.depth, 2);
+        assert_eq!(last.best_move, Some(m));
+        assert!(board.is_valid_move(m.0, m.1, false));
+        assert_eq!(bot.nodes_searched(), Some(last.nodes));
+
+        // depths are reported in order
+        let depths: Vec<u16> = seen.iter().map(|p| p.depth).collect();
+        assert!(depths.is_sorted());
+        assert_eq!(depths[0], 1);
+    }
+}
@@ -1,48 +1,153 @@
+use std::time::Duration;
+
 use crate::{
     board::Board,
     bots::{MakeMove, deep_heuristic_bot},
+    position::Position,
+    search::{Search, SearchControl},
+    sized_board::SizedBoard,
+    variant::Variant,
 };
 
-/// This bot maximizes a heuristic score (guessed by the developer)
-/// by doing a 5-deep minmax search
-pub struct Bot {}
+/// This bot plays the moves the deep heuristic bot likes least, by
+/// minimizing the result of its 5-deep minmax search. It plays badly on
+/// purpose in both variants.
+pub struct Bot {
+    nodes: u64,
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot {}
+        Bot {
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let mut search = Search::new(self.time_limit, self.control.as_deref_mut());
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            return (-1, -1);
+        }
+
+        // searching one ply deeper at a time leaves a move to play when the
+        // search is stopped early
+        for depth in 1..=DEPTH {
+            match search_moves(board, color, &moves, depth, self.variant, &mut search) {
+                Some(m) => search.finish_depth(depth, m),
+                None => break,
+            }
+            if search.stopped() {
+                break;
+            }
+        }
+
+        self.nodes = search.nodes;
+        search.best_move().unwrap_or(moves[0])
     }
 }
 
+/// Plies searched, including the bot's own move
+const DEPTH: u16 = 5;
+
 const NEG_INF_SCORE: f32 = f32::MIN;
 const POS_INF_SCORE: f32 = f32::MAX;
 
 impl MakeMove for Bot {
     fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
-        let moves = board.get_all_valid_moves(color).into_iter();
-
-        let moves = moves;
-
-        let mut best_score = NEG_INF_SCORE;
-        let mut best_move = (-1, -1);
-
-        for m in moves {
-            let mut future = board.clone();
-            future.try_place_chip(m.0, m.1, color);
-            let result = -deep_heuristic_bot::evaluate_board(
-                &future,
-                color,
-                !color,
-                4,
-                NEG_INF_SCORE,
-                POS_INF_SCORE,
-            );
-
-            if result > best_score {
-                best_move = m;
-                best_score = result;
-            }
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+/// Finds the best of `moves` by searching `depth` plies deep.
+/// Returns None if the search was stopped before it finished.
+fn search_moves<B: Position>(
+    board: &B,
+    color: bool,
+    moves: &[(i8, i8)],
+    depth: u16,
+    variant: Variant,
+    search: &mut Search,
+) -> Option<(i8, i8)> {
+    let mut best_score = NEG_INF_SCORE;
+    let mut best_move = moves[0];
+
+    for &m in moves {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        let result = -deep_heuristic_bot::evaluate_board(
+            &future,
+            color,
+            !color,
+            depth - 1,
+            NEG_INF_SCORE,
+            POS_INF_SCORE,
+            variant,
+            search,
+        );
+        if search.stopped() {
+            return None;
         }
 
-        best_move
+        if result > best_score {
+            best_move = m;
+            best_score = result;
+        }
+    }
+
+    Some(best_move)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::create_board;
+
+    #[test]
+    fn gives_up_corners_in_both_variants() {
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        for variant in [Variant::Standard, Variant::AntiOthello] {
+            let mut bot = Bot::new();
+            bot.set_variant(variant);
+            let m = bot.make_move(&board, false);
+            assert!(board.is_valid_move(m.0, m.1, false));
+            assert_ne!(m, (0, 0), "{variant:?}");
+        }
     }
 }
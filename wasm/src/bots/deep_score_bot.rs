@@ -1,58 +1,160 @@
-use crate::{board::Board, bots::MakeMove};
+use std::time::Duration;
+
+use crate::{
+    board::Board,
+    bots::MakeMove,
+    position::Position,
+    search::{Search, SearchControl},
+    sized_board::SizedBoard,
+    variant::Variant,
+};
 
 /// This bot maximizes score by doing a 5-deep minmax search
-pub struct Bot {}
+pub struct Bot {
+    nodes: u64,
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot {}
+        Bot {
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let mut search = Search::new(self.time_limit, self.control.as_deref_mut());
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            return (-1, -1);
+        }
+
+        // searching one ply deeper at a time leaves a move to play when the
+        // search is stopped early
+        for depth in 1..=DEPTH {
+            match search_moves(board, color, &moves, depth, self.variant, &mut search) {
+                Some(m) => search.finish_depth(depth, m),
+                None => break,
+            }
+            if search.stopped() {
+                break;
+            }
+        }
+
+        self.nodes = search.nodes;
+        search.best_move().unwrap_or(moves[0])
     }
 }
 
+/// Plies searched, including the bot's own move
+const DEPTH: u16 = 5;
+
 const NEG_INF_SCORE: u16 = 0;
-const POS_INF_SCORE: u16 = 64;
+const POS_INF_SCORE: u16 = u16::MAX;
 
 impl MakeMove for Bot {
     fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
-        let moves = board.get_all_valid_moves(color).into_iter();
+        self.choose_move(board, color)
+    }
 
-        let moves = moves;
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
 
-        let mut best_score = 0;
-        let mut best_move = (-1, -1);
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
 
-        for m in moves {
-            let mut future = board.clone();
-            future.try_place_chip(m.0, m.1, color);
-            let result = evaluate_board(&future, color, !color, 4, NEG_INF_SCORE, POS_INF_SCORE);
-            // let val = heuristic_score(&future, color);
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
 
-            if result > best_score {
-                best_move = m;
-                best_score = result;
-            }
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+/// Finds the best of `moves` by searching `depth` plies deep.
+/// Returns None if the search was stopped before it finished.
+fn search_moves<B: Position>(
+    board: &B,
+    color: bool,
+    moves: &[(i8, i8)],
+    depth: u16,
+    variant: Variant,
+    search: &mut Search,
+) -> Option<(i8, i8)> {
+    let mut best_score = 0;
+    let mut best_move = moves[0];
+
+    for &m in moves {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        let result = evaluate_board(
+            &future,
+            color,
+            !color,
+            depth - 1,
+            NEG_INF_SCORE,
+            POS_INF_SCORE,
+            variant,
+            search,
+        );
+        if search.stopped() {
+            return None;
         }
 
-        best_move
+        if result > best_score {
+            best_move = m;
+            best_score = result;
+        }
     }
+
+    Some(best_move)
 }
 
-fn evaluate_board(
-    board: &Board,
+#[allow(clippy::too_many_arguments)]
+fn evaluate_board<B: Position>(
+    board: &B,
     color: bool,
     next_turn_color: bool,
     depth: u16,
     alpha: u16,
     beta: u16,
+    variant: Variant,
+    search: &mut Search,
 ) -> u16 {
+    if search.visit() {
+        // out of time, the result is thrown away
+        return alpha;
+    }
+
     if depth == 0 {
-        return heuristic_score(board, color);
+        return heuristic_score(board, color, variant);
     }
 
     let next_moves = board.get_all_valid_moves(next_turn_color);
 
     if next_moves.is_empty() {
-        return evaluate_board(&board, color, !next_turn_color, depth - 1, alpha, beta);
+        return evaluate_board(
+            board,
+            color,
+            !next_turn_color,
+            depth - 1,
+            alpha,
+            beta,
+            variant,
+            search,
+        );
     }
 
     let next_moves = next_moves.into_iter();
@@ -65,8 +167,16 @@ fn evaluate_board(
             let mut future_board = board.clone();
             future_board.try_place_chip(m.0, m.1, next_turn_color);
             // this branch is (result or worse)
-            let result =
-                evaluate_board(&future_board, color, !next_turn_color, depth - 1, max, beta);
+            let result = evaluate_board(
+                &future_board,
+                color,
+                !next_turn_color,
+                depth - 1,
+                max,
+                beta,
+                variant,
+                search,
+            );
             // alphabeta = result.1;
 
             if result >= max {
@@ -95,6 +205,8 @@ fn evaluate_board(
                 depth - 1,
                 alpha,
                 min,
+                variant,
+                search,
             );
 
             if result < min {
@@ -111,17 +223,23 @@ fn evaluate_board(
     }
 }
 
-fn heuristic_score(board: &Board, color: bool) -> u16 {
-    let score = board.count_pieces(color);
-    score
+fn heuristic_score<B: Position>(board: &B, color: bool, variant: Variant) -> u16 {
+    variant.disc_score(board, color)
 }
 
 #[cfg(test)]
 mod test {
+    use std::{cell::RefCell, rc::Rc};
+
     use crate::{
         board::Board,
-        bots::deep_score_bot::{NEG_INF_SCORE, POS_INF_SCORE, evaluate_board},
+        bots::{
+            MakeMove,
+            deep_score_bot::{Bot, NEG_INF_SCORE, POS_INF_SCORE, evaluate_board},
+        },
         create_board,
+        search::{Progress, Search, SearchControl},
+        variant::Variant,
     };
 
     #[test]
@@ -233,6 +351,32 @@ mod test {
         assert_eq!(eval_board(&board, true, false, 4), 9);
     }
 
+    #[test]
+    fn reports_each_depth() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut bot = Bot::new();
+        bot.set_search_control(Some(Box::new(Record(Rc::clone(&seen)))));
+        let m = bot.make_move(&Board::new(), false);
+
+        let seen = seen.borrow();
+        let depths: Vec<u16> = seen.iter().map(|p| p.depth).collect();
+        assert!(depths.is_sorted());
+        let last = seen.last().unwrap();
+        assert_eq!(last.depth, 5);
+        assert_eq!(last.best_move, Some(m));
+        assert_eq!(bot.nodes_searched(), Some(last.nodes));
+    }
+
+    /// Never stops the search, sharing what it saw with the test
+    struct Record(Rc<RefCell<Vec<Progress>>>);
+
+    impl SearchControl for Record {
+        fn should_stop(&mut self, progress: &Progress) -> bool {
+            self.0.borrow_mut().push(progress.clone());
+            false
+        }
+    }
+
     fn eval_board(board: &Board, color: bool, next_turn_color: bool, depth: u16) -> u16 {
         evaluate_board(
             board,
@@ -241,6 +385,8 @@ mod test {
             depth,
             NEG_INF_SCORE,
             POS_INF_SCORE,
+            Variant::Standard,
+            &mut Search::new(None, None),
         )
     }
 }
@@ -1,32 +1,47 @@
-use rand::{rngs::ThreadRng, seq::SliceRandom};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 
-use crate::{board::Board, bots::MakeMove};
+use crate::{board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard};
 
 /// This bot makes random moves unless it can place a piece on the edge
 pub struct Bot {
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot { rng: rand::rng() }
+        Bot {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let mut moves = board.get_all_valid_moves(color);
+        moves.shuffle(&mut self.rng);
+
+        moves
+            .into_iter()
+            .max_by_key(|m| evaluate_move(m, board.size()))
+            .unwrap_or((-1, -1))
     }
 }
 
 impl MakeMove for Bot {
     fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
-        let mut moves = board.get_all_valid_moves(color);
-        moves.shuffle(&mut self.rng);
+        self.choose_move(board, color)
+    }
 
-        match moves.into_iter().max_by_key(|m| evaluate_move(m)) {
-            Some(x) => x,
-            None => (-1, -1),
-        }
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
 
-fn evaluate_move(m: &(i8, i8)) -> i8 {
-    let x_score = if m.0 == 0 || m.0 == 7 { 1 } else { 0 };
-    let y_score = if m.1 == 0 || m.1 == 7 { 1 } else { 0 };
+fn evaluate_move(m: &(i8, i8), size: i8) -> i8 {
+    let last = size - 1;
+    let x_score = if m.0 == 0 || m.0 == last { 1 } else { 0 };
+    let y_score = if m.1 == 0 || m.1 == last { 1 } else { 0 };
     x_score + y_score
 }
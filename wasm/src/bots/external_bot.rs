@@ -0,0 +1,228 @@
+//! A bot that asks an engine running in another process for its moves, so
+//! that engines written in any language can play the built-in bots through
+//! `BotRunner`. See `src/bin/engine.rs` for an engine wrapping a built-in
+//! bot.
+//!
+//! The bot writes one command per line to the engine's stdin:
+//!
+//! - `variant standard` or `variant antiOthello` when the rules are set
+//! - `time <ms>` when the time limit is set
+//! - `move <board> <X|O>` to ask for a move for black (X) or white (O), with
+//!   the board written as by `notation::format_board`. The engine answers
+//!   with one line holding the move, ex. "f5".
+//!
+//! The time limit counts from when the `move` command is sent until the
+//! answer is read, so it includes starting up and talking through the pipes.
+//! Engines must answer inside it, searching for a little less than `<ms>`.
+//! `src/bin/engine.rs` searches for 85% of it.
+//!
+//! An engine that doesn't answer in time, exits or answers with something
+//! other than a valid move forfeits: the bot plays off the board and
+//! `forfeit_reason` says why. The engine is stopped and the bot forfeits
+//! every move after that.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    board::Board, bots::MakeMove, notation, position::Position, sized_board::SizedBoard,
+    variant::Variant,
+};
+
+/// How long the engine may take for a move if no time limit is set
+pub const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+pub struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines from the engine's stdout, read on another thread so that
+    /// waiting for them can time out
+    lines: Receiver<String>,
+    time_limit: Duration,
+    forfeit: Option<String>,
+}
+
+impl Bot {
+    /// Starts `program` with `args` as the engine.
+    ///
+    /// Returns an error if the program can't be started.
+    pub fn new(program: &str, args: &[&str]) -> io::Result<Bot> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Bot {
+            child,
+            stdin,
+            lines,
+            time_limit: DEFAULT_TIME_LIMIT,
+            forfeit: None,
+        })
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        if self.forfeit.is_some() {
+            return (-1, -1);
+        }
+
+        match self.ask(board, color) {
+            Ok(m) => m,
+            Err(reason) => {
+                self.forfeit = Some(reason);
+                self.stop();
+                (-1, -1)
+            }
+        }
+    }
+
+    /// Asks the engine for a move. Returns why the engine forfeits if it
+    /// doesn't answer in time with a valid move.
+    fn ask<B: Position>(&mut self, board: &B, color: bool) -> Result<(i8, i8), String> {
+        let color_letter = if color { 'O' } else { 'X' };
+        self.send(&format!(
+            "move {} {color_letter}",
+            notation::format_board(board)
+        ))
+        .map_err(|_| "The engine has exited".to_string())?;
+
+        let answer = match self.lines.recv_timeout(self.time_limit) {
+            Ok(answer) => answer,
+            Err(RecvTimeoutError::Timeout) => return Err("The engine ran out of time".to_string()),
+            Err(RecvTimeoutError::Disconnected) => return Err("The engine has exited".to_string()),
+        };
+
+        let answer = answer.trim();
+        match notation::parse_move_sized(&answer.to_ascii_lowercase(), board.size()) {
+            Some((x, y)) if board.is_valid_move(x, y, color) => Ok((x, y)),
+            Some(_) => Err(format!("The engine played an invalid move: {answer}")),
+            None => Err(format!("The engine's answer isn't a move: {answer}")),
+        }
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{line}")?;
+        self.stdin.flush()
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Asks an engine in another process for its moves
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit.unwrap_or(DEFAULT_TIME_LIMIT);
+        let ms = self.time_limit.as_millis();
+        // an engine that has exited forfeits at its next move
+        let _ = self.send(&format!("time {ms}"));
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        let name = match variant {
+            Variant::Standard => "standard",
+            Variant::AntiOthello => "antiOthello",
+        };
+        let _ = self.send(&format!("variant {name}"));
+    }
+
+    fn forfeit_reason(&self) -> Option<String> {
+        self.forfeit.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bots::BotRunner, error::OthelloError};
+
+    /// An engine written as a shell script
+    fn script(script: &str) -> Box<dyn MakeMove> {
+        Box::new(Bot::new("sh", &["-c", script]).unwrap())
+    }
+
+    #[test]
+    fn plays_answers() {
+        // answers every request with d3, black's first move
+        let mut bot = script("while read command rest; do [ $command = move ] && echo D3; done");
+        bot.set_variant(Variant::AntiOthello);
+        assert_eq!(bot.make_move(&Board::new(), false), (3, 2));
+        assert_eq!(bot.forfeit_reason(), None);
+
+        assert_eq!(bot.make_move(&Board::new(), true), (-1, -1));
+        assert_eq!(
+            bot.forfeit_reason().as_deref(),
+            Some("The engine played an invalid move: D3")
+        );
+        // stays forfeited
+        assert_eq!(bot.make_move(&Board::new(), false), (-1, -1));
+    }
+
+    #[test]
+    fn forfeits() {
+        let cases = [
+            (
+                "read line; echo hello",
+                "The engine's answer isn't a move: hello",
+            ),
+            ("exit 1", "The engine has exited"),
+            ("read line; sleep 5", "The engine ran out of time"),
+        ];
+        for (engine, reason) in cases {
+            let mut bot = script(engine);
+            // long enough for the shell to start when the machine is busy
+            bot.set_time_limit(Some(Duration::from_secs(1)));
+            assert_eq!(bot.make_move(&Board::new(), false), (-1, -1), "{engine}");
+            assert_eq!(bot.forfeit_reason().as_deref(), Some(reason), "{engine}");
+        }
+
+        let mut runner = BotRunner::new(Some(script("exit 0")), Some(script("exit 0")));
+        assert_eq!(
+            runner.run_game_to_end(),
+            Err(OthelloError::Forfeit("The engine has exited".to_string()))
+        );
+        assert!(runner.game.history().is_empty());
+    }
+
+    #[test]
+    fn missing_program() {
+        assert!(Bot::new("no-such-othello-engine", &[]).is_err());
+    }
+}
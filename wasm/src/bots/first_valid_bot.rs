@@ -1,4 +1,4 @@
-use crate::{board::Board, bots::MakeMove};
+use crate::{board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard};
 
 /// This bot always chooses the first valid move.
 pub struct Bot {}
@@ -7,13 +7,10 @@ impl Bot {
     pub fn new() -> Bot {
         Bot {}
     }
-}
 
-/// This bot always chooses the first valid move
-impl MakeMove for Bot {
-    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
-        for y in 0..8 {
-            for x in 0..8 {
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        for y in 0..board.size() {
+            for x in 0..board.size() {
                 if board.is_valid_move(x, y, color) {
                     return (x, y);
                 }
@@ -23,3 +20,14 @@ impl MakeMove for Bot {
         (-1, -1)
     }
 }
+
+/// This bot always chooses the first valid move
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{board::Board, bots::MakeMove};
+use crate::{board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard};
 
 /// This bot always chooses the last valid move.
 pub struct Bot {}
@@ -7,15 +7,23 @@ impl Bot {
     pub fn new() -> Bot {
         Bot {}
     }
-}
 
-impl MakeMove for Bot {
-    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
         let valid_moves = board.get_all_valid_moves(color);
 
         match valid_moves.last() {
-            Some(x) => x.clone(),
+            Some(x) => *x,
             None => (-1, -1),
         }
     }
 }
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+}
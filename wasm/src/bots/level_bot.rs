@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Serialize;
+
+use crate::{
+    analysis,
+    board::Board,
+    bots::MakeMove,
+    error::OthelloError,
+    position::Position,
+    search::{Search, SearchControl},
+    sized_board::SizedBoard,
+    variant::Variant,
+};
+
+/// The easiest level
+pub const MIN_LEVEL: u8 = 1;
+/// The hardest level
+pub const MAX_LEVEL: u8 = 10;
+
+/// How well a level plays
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Level {
+    /// Plies searched, including the bot's own move
+    pub depth: u16,
+    /// Largest random amount added to or taken from each move's score, in
+    /// the units of `deep_heuristic_bot::heuristic_score`
+    pub noise: f32,
+    /// Chance of playing a random move instead of searching
+    pub blunder_chance: f64,
+    /// Strength measured by the `tournament` binary in a round robin with
+    /// the other levels and a few built-in bots, with the random bot at 0.
+    /// See `tournaments/levels.txt`.
+    pub elo: i32,
+}
+
+/// The levels from 1 to 10
+pub const LEVELS: [Level; MAX_LEVEL as usize] = [
+    Level {
+        depth: 1,
+        noise: 40.0,
+        blunder_chance: 0.7,
+        elo: 66,
+    },
+    Level {
+        depth: 1,
+        noise: 10.0,
+        blunder_chance: 0.3,
+        elo: 159,
+    },
+    Level {
+        depth: 2,
+        noise: 10.0,
+        blunder_chance: 0.5,
+        elo: 244,
+    },
+    Level {
+        depth: 2,
+        noise: 8.0,
+        blunder_chance: 0.3,
+        elo: 332,
+    },
+    Level {
+        depth: 3,
+        noise: 6.0,
+        blunder_chance: 0.3,
+        elo: 409,
+    },
+    Level {
+        depth: 3,
+        noise: 0.0,
+        blunder_chance: 0.0,
+        elo: 484,
+    },
+    Level {
+        depth: 4,
+        noise: 8.0,
+        blunder_chance: 0.15,
+        elo: 590,
+    },
+    Level {
+        depth: 4,
+        noise: 2.0,
+        blunder_chance: 0.07,
+        elo: 672,
+    },
+    Level {
+        depth: 5,
+        noise: 6.0,
+        blunder_chance: 0.08,
+        elo: 759,
+    },
+    Level {
+        depth: 5,
+        noise: 0.0,
+        blunder_chance: 0.0,
+        elo: 829,
+    },
+];
+
+/// The settings of a level from 1 to 10.
+/// Returns an error for other levels.
+pub fn level(level: u8) -> Result<Level, OthelloError> {
+    match level {
+        MIN_LEVEL..=MAX_LEVEL => Ok(LEVELS[(level - 1) as usize]),
+        _ => Err(OthelloError::InvalidLevel(level)),
+    }
+}
+
+/// The name `bots::create_bot` knows a level by, ex. "level_3"
+pub fn name(level: u8) -> String {
+    format!("level_{level}")
+}
+
+/// The level a name made by `name` is for, even if there is no such
+/// level. None for names of other bots.
+pub fn parse_name(name: &str) -> Option<u8> {
+    name.strip_prefix("level_")?.parse().ok()
+}
+
+/// This bot plays at a chosen level of difficulty: it searches a few plies
+/// with the deep heuristic, misjudges moves by a random amount and
+/// sometimes plays a random move
+pub struct Bot {
+    level: Level,
+    rng: StdRng,
+    nodes: u64,
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
+
+impl Bot {
+    /// A bot playing at `level`, from 1 to 10.
+    /// Returns an error for other levels.
+    pub fn new(level: u8) -> Result<Bot, OthelloError> {
+        Ok(Bot::with_level(self::level(level)?))
+    }
+
+    /// A bot playing with settings of its own, ex. to try out new levels
+    pub fn with_level(level: Level) -> Bot {
+        Bot {
+            level,
+            rng: StdRng::from_os_rng(),
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            return (-1, -1);
+        }
+        if self.rng.random_bool(self.level.blunder_chance) {
+            self.nodes = 0;
+            return moves[self.rng.random_range(0..moves.len())];
+        }
+
+        let mut search = Search::new(self.time_limit, self.control.as_deref_mut());
+        let mut ranked = Vec::new();
+        // searching one ply deeper at a time leaves moves to choose from
+        // when the search is stopped early
+        for depth in 1..=self.level.depth {
+            match analysis::rank_moves(board, color, depth, self.variant, &mut search) {
+                Some(deeper) => {
+                    search.finish_depth(depth, deeper[0].0);
+                    ranked = deeper;
+                }
+                None => break,
+            }
+            if search.stopped() {
+                break;
+            }
+        }
+        self.nodes = search.nodes;
+
+        let noise = self.level.noise;
+        ranked
+            .into_iter()
+            .map(|(m, score)| (m, score + self.rng.random_range(-noise..=noise)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(moves[0], |(m, _)| m)
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bots::create_bot, create_board, search::Progress};
+
+    #[test]
+    fn levels() {
+        assert_eq!(level(0), Err(OthelloError::InvalidLevel(0)));
+        assert_eq!(level(11), Err(OthelloError::InvalidLevel(11)));
+        assert!(Bot::new(11).is_err());
+        assert_eq!(parse_name(&name(11)), Some(11));
+        assert_eq!(parse_name("random_bot"), None);
+        assert_eq!(level(10).unwrap().noise, 0.0);
+
+        // a deeper search makes up for more mistakes, so only the depth and
+        // the measured strength go up with every level
+        for pair in LEVELS.windows(2) {
+            assert!(pair[0].depth <= pair[1].depth);
+            assert!(pair[0].elo < pair[1].elo);
+        }
+
+        assert_eq!(name(3), "level_3");
+        assert!(create_bot(&name(MAX_LEVEL)).is_some());
+        assert!(create_bot("level_0").is_none());
+    }
+
+    #[test]
+    fn top_level_takes_corners() {
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+        let mut bot = Bot::new(MAX_LEVEL).unwrap();
+        assert_eq!(bot.make_move(&board, false), (0, 0));
+        assert!(bot.nodes_searched().unwrap() > 0);
+    }
+
+    /// Stops once a depth is finished
+    struct StopAtDepth(u16);
+
+    impl SearchControl for StopAtDepth {
+        fn should_stop(&mut self, progress: &Progress) -> bool {
+            progress.depth >= self.0
+        }
+    }
+
+    #[test]
+    fn stops_with_the_deepest_ranking() {
+        let mut bot = Bot::new(MAX_LEVEL).unwrap();
+        bot.set_search_control(Some(Box::new(StopAtDepth(1))));
+        let board = Board::new();
+        let m = bot.make_move(&board, false);
+        assert!(board.is_valid_move(m.0, m.1, false));
+        let stopped = bot.nodes_searched().unwrap();
+
+        bot.set_search_control(None);
+        bot.make_move(&board, false);
+        assert!(bot.nodes_searched().unwrap() > stopped);
+    }
+}
@@ -0,0 +1,425 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use web_time::Instant;
+
+use crate::{
+    board::Board,
+    bots::MakeMove,
+    position::Position,
+    search::{Progress, SearchControl},
+    sized_board::SizedBoard,
+    variant::Variant,
+};
+
+/// This bot runs a Monte Carlo tree search (UCT), judging moves by the
+/// results of many quickly played out games
+pub struct Bot {
+    config: Config,
+    rng: StdRng,
+    root_stats: Vec<MoveStats>,
+    /// Tree nodes added plus positions played through in playouts
+    nodes: u64,
+    /// Stops the search early, whatever the budget
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
+
+/// How long the search runs for each move
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Iterations(u32),
+    Millis(u32),
+}
+
+/// Iterations between polls of the search control
+const CONTROL_INTERVAL: u32 = 64;
+
+/// How moves are chosen when playing out a game from a new tree node
+#[derive(Clone, Copy, Debug)]
+pub enum Playout {
+    /// Plays a valid move randomly
+    Random,
+    /// Plays corners if possible, otherwise plays a valid move randomly
+    CornersFirst,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub budget: Budget,
+    /// Higher values explore less visited moves more often
+    pub exploration: f32,
+    pub playout: Playout,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            budget: Budget::Iterations(1000),
+            exploration: std::f32::consts::SQRT_2,
+            playout: Playout::Random,
+        }
+    }
+}
+
+/// Search results for a move at the root of the tree
+#[derive(Clone, Debug)]
+pub struct MoveStats {
+    pub m: (i8, i8),
+    pub visits: u32,
+    /// Average result of the playouts through this move.
+    /// 1 = win, 0.5 = tie, 0 = loss
+    pub win_rate: f32,
+}
+
+struct Node<B> {
+    board: B,
+    /// The move that lead to this node. (-1, -1) for the root.
+    m: (i8, i8),
+    /// The color that played the move leading to this node
+    moved_color: bool,
+    /// The color to play next, None if the game is over
+    to_move: Option<bool>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<(i8, i8)>,
+    visits: u32,
+    /// Sum of results from the perspective of `moved_color`
+    wins: f32,
+}
+
+impl<B: Position> Node<B> {
+    fn new(board: B, m: (i8, i8), moved_color: bool, parent: Option<usize>) -> Node<B> {
+        let to_move = board.next_turn(moved_color);
+        let untried = match to_move {
+            Some(color) => board.get_all_valid_moves(color),
+            None => Vec::new(),
+        };
+
+        Node {
+            board,
+            m,
+            moved_color,
+            to_move,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0f32,
+        }
+    }
+}
+
+impl Bot {
+    pub fn new() -> Bot {
+        Bot::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Bot {
+        Bot {
+            config,
+            rng: StdRng::from_os_rng(),
+            root_stats: Vec::new(),
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    /// Visit counts and win rates of each root move from the last search,
+    /// ordered from most to least visited
+    pub fn root_stats(&self) -> &[MoveStats] {
+        &self.root_stats
+    }
+
+    fn search<B: Position>(&mut self, board: &B, color: bool) -> Vec<Node<B>> {
+        // the root is treated as if the opponent just moved, so that `color` plays next
+        let mut root = Node::new(board.clone(), (-1, -1), !color, None);
+        root.to_move = Some(color);
+        root.untried = board.get_all_valid_moves(color);
+
+        let mut tree = vec![root];
+        let start = Instant::now();
+        let mut iterations = 0;
+
+        loop {
+            match self.config.budget {
+                Budget::Iterations(max) => {
+                    if iterations >= max {
+                        break;
+                    }
+                }
+                Budget::Millis(max) => {
+                    // always finish at least one iteration so there's a move to play
+                    if iterations > 0 && start.elapsed().as_millis() >= u128::from(max) {
+                        break;
+                    }
+                }
+            }
+
+            if iterations > 0
+                && self
+                    .time_limit
+                    .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                break;
+            }
+
+            if iterations > 0 && iterations.is_multiple_of(CONTROL_INTERVAL) {
+                let progress = Progress {
+                    depth: 0,
+                    best_move: most_visited(&tree),
+                    nodes: self.nodes,
+                };
+                if let Some(control) = self.control.as_mut()
+                    && control.should_stop(&progress)
+                {
+                    break;
+                }
+            }
+
+            let leaf = self.select_and_expand(&mut tree);
+            let result = self.playout(&tree[leaf]);
+            backpropagate(&mut tree, leaf, result);
+            iterations += 1;
+        }
+
+        tree
+    }
+
+    /// Walks down the tree by UCT score until a node with untried moves is
+    /// found, then adds a child for one of those moves.
+    fn select_and_expand<B: Position>(&mut self, tree: &mut Vec<Node<B>>) -> usize {
+        let mut curr = 0;
+
+        loop {
+            let node = &tree[curr];
+
+            if !node.untried.is_empty() {
+                let i = self.rng.random_range(0..node.untried.len());
+                let m = tree[curr].untried.swap_remove(i);
+                let color = tree[curr]
+                    .to_move
+                    .expect("nodes with moves have a player to move");
+
+                let mut future = tree[curr].board.clone();
+                future.try_place_chip(m.0, m.1, color);
+
+                self.nodes += 1;
+                let child = tree.len();
+                tree.push(Node::new(future, m, color, Some(curr)));
+                tree[curr].children.push(child);
+                return child;
+            }
+
+            if node.children.is_empty() {
+                // game over
+                return curr;
+            }
+
+            let log_visits = (node.visits as f32).ln();
+            curr = *node
+                .children
+                .iter()
+                .max_by(|a, b| {
+                    let a = uct_score(&tree[**a], log_visits, self.config.exploration);
+                    let b = uct_score(&tree[**b], log_visits, self.config.exploration);
+                    a.total_cmp(&b)
+                })
+                .expect("children is not empty");
+        }
+    }
+
+    /// Plays the game to the end. Returns the result for the player that
+    /// moved into the node.
+    fn playout<B: Position>(&mut self, node: &Node<B>) -> f32 {
+        let mut board = node.board.clone();
+        let mut turn = node.to_move;
+        let size = board.size();
+
+        while let Some(color) = turn {
+            let moves = board.get_all_valid_moves(color);
+            let m = match self.config.playout {
+                Playout::Random => moves[self.rng.random_range(0..moves.len())],
+                Playout::CornersFirst => match moves.iter().find(|m| is_corner(m, size)) {
+                    Some(m) => *m,
+                    None => moves[self.rng.random_range(0..moves.len())],
+                },
+            };
+
+            board.try_place_chip(m.0, m.1, color);
+            turn = board.next_turn(color);
+            self.nodes += 1;
+        }
+
+        match self.variant.winner(&board) {
+            Some(winner) if winner == node.moved_color => 1f32,
+            Some(_) => 0f32,
+            None => 0.5f32,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        self.nodes = 0;
+
+        if !board.has_valid_move(color) {
+            self.root_stats.clear();
+            return (-1, -1);
+        }
+
+        let tree = self.search(board, color);
+
+        self.root_stats = tree[0]
+            .children
+            .iter()
+            .map(|i| {
+                let child = &tree[*i];
+                MoveStats {
+                    m: child.m,
+                    visits: child.visits,
+                    win_rate: child.wins / child.visits as f32,
+                }
+            })
+            .collect();
+        self.root_stats.sort_by_key(|s| std::cmp::Reverse(s.visits));
+
+        self.root_stats[0].m
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+/// The root move with the most visits so far
+fn most_visited<B>(tree: &[Node<B>]) -> Option<(i8, i8)> {
+    tree[0]
+        .children
+        .iter()
+        .max_by_key(|i| tree[**i].visits)
+        .map(|i| tree[*i].m)
+}
+
+fn uct_score<B>(node: &Node<B>, parent_log_visits: f32, exploration: f32) -> f32 {
+    let visits = node.visits as f32;
+    node.wins / visits + exploration * (parent_log_visits / visits).sqrt()
+}
+
+fn backpropagate<B>(tree: &mut [Node<B>], leaf: usize, result: f32) {
+    let leaf_color = tree[leaf].moved_color;
+    let mut curr = Some(leaf);
+
+    while let Some(i) = curr {
+        let node = &mut tree[i];
+        node.visits += 1;
+        node.wins += if node.moved_color == leaf_color {
+            result
+        } else {
+            1f32 - result
+        };
+        curr = node.parent;
+    }
+}
+
+fn is_corner(m: &(i8, i8), size: i8) -> bool {
+    let last = size - 1;
+    (m.0 == 0 || m.0 == last) && (m.1 == 0 || m.1 == last)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        board::Board,
+        bots::{
+            MakeMove,
+            mcts_bot::{Bot, Budget, Config, Playout},
+        },
+        create_board,
+    };
+
+    fn bot_with_iterations(iterations: u32) -> Bot {
+        Bot::with_config(Config {
+            budget: Budget::Iterations(iterations),
+            ..Config::default()
+        })
+    }
+
+    #[test]
+    pub fn visits_add_up_to_iterations() {
+        let mut bot = bot_with_iterations(200);
+        let m = bot.make_move(&Board::new(), false);
+
+        let stats = bot.root_stats();
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.iter().map(|s| s.visits).sum::<u32>(), 200);
+        assert_eq!(stats[0].m, m);
+    }
+
+    #[test]
+    pub fn plays_only_valid_move() {
+        // X can only play (0, 0), which is also the end of the game
+        let board = create_board!(
+            [_ O O O O O O X],
+            [O O O O O O O X],
+            [O O O O O O O X],
+            [O O O O O O O X],
+            [O O O O O O O X],
+            [O O O O O O O X],
+            [O O O O O O O X],
+            [X X X X X X X _],
+        );
+
+        let mut bot = Bot::with_config(Config {
+            budget: Budget::Iterations(100),
+            playout: Playout::CornersFirst,
+            ..Config::default()
+        });
+        assert_eq!(bot.make_move(&board, false), (0, 0));
+    }
+
+    #[test]
+    pub fn no_valid_moves() {
+        let board = create_board!(
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ X O O O O],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+            [_ _ _ O _ _ _ _],
+        );
+
+        let mut bot = bot_with_iterations(50);
+        assert_eq!(bot.make_move(&board, false), (-1, -1));
+        assert!(bot.root_stats().is_empty());
+    }
+}
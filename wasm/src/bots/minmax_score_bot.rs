@@ -1,34 +1,53 @@
-use rand::{rngs::ThreadRng, seq::SliceRandom};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 
-use crate::{board::Board, bots::MakeMove};
+use crate::{
+    board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard, variant::Variant,
+};
 
 /// This bot picks the move that gives it the most score after the best opponent move
 pub struct Bot {
-    rng: ThreadRng,
+    rng: StdRng,
+    variant: Variant,
 }
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot { rng: rand::rng() }
+        Bot {
+            rng: StdRng::from_os_rng(),
+            variant: Variant::Standard,
+        }
     }
-}
 
-impl MakeMove for Bot {
-    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
         let mut moves = board.get_all_valid_moves(color);
         moves.shuffle(&mut self.rng);
 
-        match moves
+        moves
             .into_iter()
-            .max_by_key(|m| evaluate_move(m, &board, color))
-        {
-            Some(x) => x,
-            None => (-1, -1),
-        }
+            .max_by_key(|m| evaluate_move(m, board, color, self.variant))
+            .unwrap_or((-1, -1))
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
     }
 }
 
-fn evaluate_move(m: &(i8, i8), board: &Board, color: bool) -> u16 {
+fn evaluate_move<B: Position>(m: &(i8, i8), board: &B, color: bool, variant: Variant) -> u16 {
     let mut future = board.clone();
     future.try_place_chip(m.0, m.1, color);
     let opponent_moves = future.get_all_valid_moves(!color);
@@ -38,15 +57,15 @@ fn evaluate_move(m: &(i8, i8), board: &Board, color: bool) -> u16 {
         .map(|op_m| {
             let mut future2 = future.clone();
             future2.try_place_chip(op_m.0, op_m.1, !color);
-            heuristic_score(&future2, color)
+            heuristic_score(&future2, color, variant)
         })
         .min()
     {
-        None => heuristic_score(&future, color), // to be more accurate, this should be the score of the best next move
+        None => heuristic_score(&future, color, variant), // to be more accurate, this should be the score of the best next move
         Some(x) => x,
     }
 }
 
-fn heuristic_score(board: &Board, color: bool) -> u16 {
-    board.count_pieces(color)
+fn heuristic_score<B: Position>(board: &B, color: bool, variant: Variant) -> u16 {
+    variant.disc_score(board, color)
 }
@@ -1,26 +1,40 @@
-use rand::{Rng, rngs::ThreadRng};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
-use crate::{board::Board, bots::MakeMove};
+use crate::{board::Board, bots::MakeMove, position::Position, sized_board::SizedBoard};
 
 /// This bot choses a random move
 pub struct Bot {
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl Bot {
     pub fn new() -> Bot {
-        Bot { rng: rand::rng() }
+        Bot {
+            rng: StdRng::from_os_rng(),
+        }
     }
-}
 
-impl MakeMove for Bot {
-    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
         let valid_moves = board.get_all_valid_moves(color);
 
         if valid_moves.is_empty() {
             return (-1, -1);
         }
 
-        valid_moves[self.rng.random_range(0..valid_moves.len())].clone()
+        valid_moves[self.rng.random_range(0..valid_moves.len())]
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
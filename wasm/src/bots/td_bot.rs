@@ -0,0 +1,276 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    board::Board,
+    bots::MakeMove,
+    position::Position,
+    search::{Search, SearchControl},
+    sized_board::SizedBoard,
+    td_learning::Weights,
+    variant::Variant,
+};
+
+/// Score of a won game, better than any guessed result
+const WIN_SCORE: f32 = 2f32;
+
+/// This bot does an alpha-beta search with an evaluation learned by
+/// self-play, see `td_learning`. The weights are learned in standard games;
+/// `td_learning` explains how they are used in Anti-Othello.
+pub struct Bot {
+    weights: Weights,
+    config: Config,
+    rng: StdRng,
+    nodes: u64,
+    time_limit: Option<Duration>,
+    control: Option<Box<dyn SearchControl>>,
+    variant: Variant,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Plies searched, including the bot's own move
+    pub depth: u16,
+    /// Chance of playing a random move instead of searching, for self-play
+    pub exploration: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            depth: 4,
+            exploration: 0f64,
+        }
+    }
+}
+
+impl Bot {
+    pub fn new(weights: Weights) -> Bot {
+        Bot::with_config(weights, Config::default())
+    }
+
+    pub fn with_config(weights: Weights, config: Config) -> Bot {
+        Bot {
+            weights,
+            config,
+            rng: StdRng::from_os_rng(),
+            nodes: 0,
+            time_limit: None,
+            control: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn choose_move<B: Position>(&mut self, board: &B, color: bool) -> (i8, i8) {
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            return (-1, -1);
+        }
+        if self.rng.random_bool(self.config.exploration) {
+            return moves[self.rng.random_range(0..moves.len())];
+        }
+
+        let mut search = Search::new(self.time_limit, self.control.as_deref_mut());
+        // searching one ply deeper at a time leaves a move to play when the
+        // search is stopped early
+        for depth in 1..=self.config.depth {
+            match search_moves(
+                board,
+                color,
+                &moves,
+                depth,
+                &self.weights,
+                self.variant,
+                &mut search,
+            ) {
+                Some(m) => search.finish_depth(depth, m),
+                None => break,
+            }
+            if search.stopped() {
+                break;
+            }
+        }
+
+        self.nodes = search.nodes;
+        search.best_move().unwrap_or(moves[0])
+    }
+}
+
+impl MakeMove for Bot {
+    fn make_move(&mut self, board: &Board, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn make_move_sized(&mut self, board: &SizedBoard, color: bool) -> (i8, i8) {
+        self.choose_move(board, color)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn nodes_searched(&self) -> Option<u64> {
+        Some(self.nodes)
+    }
+
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+
+    fn set_search_control(&mut self, control: Option<Box<dyn SearchControl>>) {
+        self.control = control;
+    }
+
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+}
+
+/// Finds the best of `moves` by searching `depth` plies deep.
+/// Returns None if the search was stopped before it finished.
+fn search_moves<B: Position>(
+    board: &B,
+    color: bool,
+    moves: &[(i8, i8)],
+    depth: u16,
+    weights: &Weights,
+    variant: Variant,
+    search: &mut Search,
+) -> Option<(i8, i8)> {
+    let mut best_score = f32::MIN;
+    let mut best_move = moves[0];
+
+    for &m in moves {
+        let mut future = board.clone();
+        future.try_place_chip(m.0, m.1, color);
+        let score = -negamax(
+            &future,
+            !color,
+            depth - 1,
+            -WIN_SCORE,
+            -best_score.max(-WIN_SCORE),
+            weights,
+            variant,
+            search,
+        );
+        if search.stopped() {
+            return None;
+        }
+
+        if score > best_score {
+            best_move = m;
+            best_score = score;
+        }
+    }
+
+    Some(best_move)
+}
+
+/// Scores the board for `color`, who is to move, with an alpha-beta search
+/// `depth` plies deep. Finished games score the result, others the
+/// learned guess of it.
+#[allow(clippy::too_many_arguments)]
+fn negamax<B: Position>(
+    board: &B,
+    color: bool,
+    depth: u16,
+    mut alpha: f32,
+    beta: f32,
+    weights: &Weights,
+    variant: Variant,
+    search: &mut Search,
+) -> f32 {
+    if search.visit() {
+        // out of time, the result is thrown away
+        return alpha;
+    }
+
+    let moves = board.get_all_valid_moves(color);
+    if moves.is_empty() {
+        if !board.has_valid_move(!color) {
+            return match variant.winner(board) {
+                Some(winner) if winner == color => WIN_SCORE,
+                Some(_) => -WIN_SCORE,
+                None => 0f32,
+            };
+        }
+        // a skip isn't a move, so it doesn't use up depth
+        return -negamax(
+            board, !color, depth, -beta, -alpha, weights, variant, search,
+        );
+    }
+
+    if depth == 0 {
+        return weights.evaluate(board, color, variant);
+    }
+
+    for (x, y) in moves {
+        let mut future = board.clone();
+        future.try_place_chip(x, y, color);
+        let score = -negamax(
+            &future,
+            !color,
+            depth - 1,
+            -beta,
+            -alpha,
+            weights,
+            variant,
+            search,
+        );
+
+        if score > alpha {
+            alpha = score;
+            if alpha >= beta {
+                // the opponent won't allow this position, stop search
+                break;
+            }
+        }
+    }
+
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_board, td_learning::SQUARE_CLASSES};
+
+    #[test]
+    fn follows_weights() {
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        // weights that only like corners
+        let mut weights = Weights::new();
+        for stage in &mut weights.stages {
+            stage[0] = 1f32;
+        }
+        let mut bot = Bot::with_config(
+            weights.clone(),
+            Config {
+                depth: 1,
+                ..Config::default()
+            },
+        );
+        assert_eq!(bot.make_move(&board, false), (0, 0));
+
+        // weights that only like mobility still search for wins
+        let mut weights = Weights::new();
+        for stage in &mut weights.stages {
+            stage[SQUARE_CLASSES] = 1f32;
+        }
+        let mut bot = Bot::new(weights);
+        let m = bot.make_move(&Board::new(), false);
+        assert!(Board::new().is_valid_move(m.0, m.1, false));
+        assert!(bot.nodes_searched().unwrap() > 0);
+    }
+}
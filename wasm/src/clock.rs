@@ -0,0 +1,211 @@
+//! Time controls: how long each player may think, over the whole game or
+//! for each move, and the clocks that keep track of it. A player whose
+//! clock runs out loses on time.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use web_time::Instant;
+
+use crate::error::OthelloError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// `total` for the whole game, with `increment` added after each move
+    Total {
+        total: Duration,
+        increment: Duration,
+    },
+    /// The same time for every move. Time that isn't used isn't kept.
+    PerMove(Duration),
+}
+
+impl TimeControl {
+    /// The time on each player's clock at the start of the game
+    fn initial(self) -> Duration {
+        match self {
+            TimeControl::Total { total, .. } => total,
+            TimeControl::PerMove(limit) => limit,
+        }
+    }
+}
+
+/// How long a player should think about its next move with `remaining` on
+/// its clock and about `moves_left` moves of its own still to play: an
+/// even share of the remaining time plus the increment. A tenth is kept
+/// back for the time it takes to play the move.
+pub fn plan_move_time(control: TimeControl, remaining: Duration, moves_left: u32) -> Duration {
+    let planned = match control {
+        TimeControl::Total { increment, .. } => remaining / moves_left.max(1) + increment,
+        TimeControl::PerMove(_) => remaining,
+    };
+    planned.min(remaining) * 9 / 10
+}
+
+/// The clocks of both players
+#[derive(Clone, Debug)]
+pub struct Clock {
+    control: TimeControl,
+    /// Time left for black and white, not counting the running clock
+    remaining: [Duration; 2],
+    /// The player whose clock is running and when it was started
+    running: Option<(bool, Instant)>,
+}
+
+/// The clocks as the page shows them
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockState {
+    pub black_ms: u32,
+    pub white_ms: u32,
+    /// The player whose clock is running
+    pub running: Option<bool>,
+    /// The player who ran out of time and lost
+    pub flagged: Option<bool>,
+}
+
+impl Clock {
+    /// Clocks for a game with `control`. Neither clock is running.
+    pub fn new(control: TimeControl) -> Clock {
+        Clock {
+            control,
+            remaining: [control.initial(); 2],
+            running: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Time left for `color`, counting the time it has been thinking if its
+    /// clock is running
+    pub fn remaining(&self, color: bool) -> Duration {
+        let left = self.remaining[color as usize];
+        match self.running {
+            Some((running, since)) if running == color => left.saturating_sub(since.elapsed()),
+            _ => left,
+        }
+    }
+
+    /// The player whose clock is running
+    pub fn running(&self) -> Option<bool> {
+        self.running.map(|(color, _)| color)
+    }
+
+    /// The player who has run out of time, if one has
+    pub fn flagged(&self) -> Option<bool> {
+        [false, true]
+            .into_iter()
+            .find(|&color| self.remaining(color).is_zero())
+    }
+
+    /// Starts `color`'s clock, or stops both clocks for None. Time used by
+    /// the player whose clock was running is taken off its clock without
+    /// an increment, ex. when moves are taken back.
+    pub fn start(&mut self, color: Option<bool>) {
+        if let Some((running, since)) = self.running {
+            let left = &mut self.remaining[running as usize];
+            *left = left.saturating_sub(since.elapsed());
+        }
+        self.running = color.map(|color| (color, Instant::now()));
+    }
+
+    /// Stops `color`'s clock after it has moved and starts `next`'s clock.
+    ///
+    /// Returns an error if `color` ran out of time before moving.
+    pub fn press(&mut self, color: bool, next: Option<bool>) -> Result<(), OthelloError> {
+        let used = match self.running.take() {
+            Some((running, since)) if running == color => since.elapsed(),
+            _ => Duration::ZERO,
+        };
+        self.use_time(color, used)?;
+        self.running = next.map(|color| (color, Instant::now()));
+        Ok(())
+    }
+
+    pub fn state(&self) -> ClockState {
+        ClockState {
+            black_ms: self.remaining(false).as_millis() as u32,
+            white_ms: self.remaining(true).as_millis() as u32,
+            running: self.running(),
+            flagged: self.flagged(),
+        }
+    }
+
+    /// Takes `used` off `color`'s clock for a move and adds the increment
+    fn use_time(&mut self, color: bool, used: Duration) -> Result<(), OthelloError> {
+        let left = &mut self.remaining[color as usize];
+        if used >= *left {
+            *left = Duration::ZERO;
+            return Err(OthelloError::OutOfTime(color));
+        }
+
+        *left = match self.control {
+            TimeControl::Total { increment, .. } => *left - used + increment,
+            TimeControl::PerMove(limit) => limit,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn increments_and_flags() {
+        let mut clock = Clock::new(TimeControl::Total {
+            total: 10 * SECOND,
+            increment: 2 * SECOND,
+        });
+        clock.use_time(false, 3 * SECOND).unwrap();
+        assert_eq!(clock.remaining(false), 9 * SECOND);
+        assert_eq!(clock.remaining(true), 10 * SECOND);
+        assert_eq!(clock.flagged(), None);
+
+        assert_eq!(
+            clock.use_time(true, 10 * SECOND),
+            Err(OthelloError::OutOfTime(true))
+        );
+        assert_eq!(clock.flagged(), Some(true));
+        assert_eq!(clock.state().white_ms, 0);
+
+        let mut clock = Clock::new(TimeControl::PerMove(SECOND));
+        clock.use_time(false, SECOND / 2).unwrap();
+        assert_eq!(clock.remaining(false), SECOND);
+    }
+
+    #[test]
+    fn running_clocks() {
+        let mut clock = Clock::new(TimeControl::PerMove(SECOND / 10));
+        clock.start(Some(false));
+        assert_eq!(clock.running(), Some(false));
+        clock.press(false, Some(true)).unwrap();
+        assert_eq!(clock.running(), Some(true));
+
+        std::thread::sleep(SECOND / 5);
+        assert_eq!(clock.flagged(), Some(true));
+        assert_eq!(
+            clock.press(true, Some(false)),
+            Err(OthelloError::OutOfTime(true))
+        );
+        assert_eq!(clock.running(), None);
+    }
+
+    #[test]
+    fn plans() {
+        let control = TimeControl::Total {
+            total: 60 * SECOND,
+            increment: SECOND,
+        };
+        assert_eq!(plan_move_time(control, 30 * SECOND, 10), 36 * SECOND / 10);
+        assert_eq!(plan_move_time(control, SECOND / 2, 0), 9 * SECOND / 20);
+        assert_eq!(
+            plan_move_time(TimeControl::PerMove(SECOND), SECOND, 30),
+            9 * SECOND / 10
+        );
+    }
+}
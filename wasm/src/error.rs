@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+/// Why a move or a request about a game failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OthelloError {
+    /// The square is not on the board
+    OutOfBounds,
+    /// There is already a piece on the square
+    Occupied,
+    /// No piece can ever be placed on the square
+    Blocked,
+    /// The move doesn't flip any pieces
+    NoFlips,
+    /// It is the other player's turn
+    WrongTurn,
+    /// Neither player can move
+    GameOver,
+    /// The player asked to move is not a bot
+    NotABot,
+    /// There is no bot with the name
+    UnknownBot(String),
+    /// There is no game with the id
+    NoGame(u32),
+    /// A position couldn't be read, see `notation::parse_board`
+    InvalidBoard,
+    /// A list of moves couldn't be read, see `notation::parse_moves`
+    InvalidRecord,
+    /// Boards can't be this size, see `sized_board::SizedBoard::new`
+    UnsupportedSize(u8),
+    /// The request only works on 8x8 boards and the game is on a board of
+    /// this size
+    NotEightByEight(u8),
+    /// Handicaps give 0 to 4 corners, see `setup::add_handicap`
+    InvalidHandicap(u8),
+    /// A bot gave up its game, ex. because the engine behind it crashed
+    Forfeit(String),
+    /// The player of this color ran out of time and lost the game
+    OutOfTime(bool),
+    /// Difficulty levels go from 1 to 10, see `bots::level_bot`
+    InvalidLevel(u8),
+}
+
+impl Display for OthelloError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OthelloError::OutOfBounds => write!(f, "The square is not on the board"),
+            OthelloError::Occupied => write!(f, "The square is already occupied"),
+            OthelloError::Blocked => write!(f, "The square is blocked"),
+            OthelloError::NoFlips => write!(f, "The move doesn't flip any pieces"),
+            OthelloError::WrongTurn => write!(f, "It isn't that player's turn"),
+            OthelloError::GameOver => write!(f, "The game is over"),
+            OthelloError::NotABot => write!(f, "That player isn't a bot"),
+            OthelloError::UnknownBot(name) => write!(f, "Unknown bot: {name}"),
+            OthelloError::NoGame(id) => write!(f, "No game with id {id}"),
+            OthelloError::InvalidBoard => write!(f, "The board couldn't be read"),
+            OthelloError::InvalidRecord => write!(f, "The moves couldn't be read"),
+            OthelloError::UnsupportedSize(size) => {
+                write!(f, "Boards can't be {size}x{size}")
+            }
+            OthelloError::NotEightByEight(size) => {
+                write!(f, "This only works on 8x8 boards, not {size}x{size}")
+            }
+            OthelloError::InvalidHandicap(corners) => {
+                write!(f, "A handicap can give 0 to 4 corners, not {corners}")
+            }
+            OthelloError::Forfeit(reason) => write!(f, "The bot forfeited: {reason}"),
+            OthelloError::OutOfTime(color) => {
+                let player = if *color { "White" } else { "Black" };
+                write!(f, "{player} ran out of time")
+            }
+            OthelloError::InvalidLevel(level) => {
+                write!(f, "Levels go from 1 to 10, not {level}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OthelloError {}
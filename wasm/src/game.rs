@@ -0,0 +1,401 @@
+use crate::{board::Board, error::OthelloError, notation, position::Position, variant::Variant};
+
+/// A turn in the game: a move, or a skip when the player had no valid moves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ply {
+    pub color: bool,
+    /// None for a skip
+    pub m: Option<(i8, i8)>,
+}
+
+/// A game from the starting position, with its move history.
+///
+/// Skips are played automatically when a player has no valid moves, so the
+/// history always alternates between the players, starting with black
+/// unless the game was set up for white to move first.
+///
+/// Games are on the 8x8 `Board` unless another `Position` is given.
+pub struct Game<B: Position = Board> {
+    variant: Variant,
+    start: B,
+    /// The color to play first, None if no one can move from the start
+    start_turn: Option<bool>,
+    board: B,
+    turn: Option<bool>,
+    history: Vec<Ply>,
+    /// Plies that were undone, most recently undone last
+    redo_stack: Vec<Ply>,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::with_variant(Variant::Standard)
+    }
+
+    pub fn with_variant(variant: Variant) -> Game {
+        Game::from_start(Board::new(), false, variant)
+    }
+}
+
+impl<B: Position> Game<B> {
+    /// Starts a game from `start` with `first` to play, or the other color
+    /// if `first` has no valid moves
+    pub fn from_start(start: B, first: bool, variant: Variant) -> Game<B> {
+        let start_turn = start.next_turn(!first);
+        Game {
+            variant,
+            start_turn,
+            turn: start_turn,
+            board: start.clone(),
+            start,
+            history: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// A new game from the same start, with the same rules
+    pub fn restarted(&self) -> Game<B> {
+        Game::from_start(
+            self.start.clone(),
+            self.start_turn.unwrap_or(false),
+            self.variant,
+        )
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The position the game started from
+    pub fn start(&self) -> &B {
+        &self.start
+    }
+
+    /// The color that played first. None if no one could move from the
+    /// start.
+    pub fn start_turn(&self) -> Option<bool> {
+        self.start_turn
+    }
+
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// The color to play next. None if the game is over.
+    pub fn turn(&self) -> Option<bool> {
+        self.turn
+    }
+
+    /// All plies played so far, including skips
+    pub fn history(&self) -> &[Ply] {
+        &self.history
+    }
+
+    /// Plays a move for `color`. Clears the plies that could be redone.
+    ///
+    /// Returns an error if it isn't `color`'s turn or the move isn't valid.
+    pub fn play(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        self.apply(x, y, color)?;
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    /// Takes back the last move, along with any skips after it.
+    ///
+    /// Returns false if there are no moves to take back.
+    pub fn undo(&mut self) -> bool {
+        if !self.history.iter().any(|ply| ply.m.is_some()) {
+            return false;
+        }
+
+        while let Some(ply) = self.history.pop() {
+            self.redo_stack.push(ply);
+            if ply.m.is_some() {
+                break;
+            }
+        }
+
+        self.replay();
+        true
+    }
+
+    /// Plays the last move taken back, along with any skips after it.
+    ///
+    /// Returns false if there are no moves to play again.
+    pub fn redo(&mut self) -> bool {
+        // a skip split from the move before it belongs with that move
+        while self.redo_stack.last().is_some_and(|ply| ply.m.is_none()) {
+            let skip = self.redo_stack.pop().expect("checked above");
+            self.history.push(skip);
+        }
+
+        let Some(Ply {
+            color,
+            m: Some((x, y)),
+        }) = self.redo_stack.pop()
+        else {
+            return false;
+        };
+        self.apply(x, y, color)
+            .expect("undone moves were valid in the same position");
+
+        // skips are played again by `apply`
+        while self.redo_stack.last().is_some_and(|ply| ply.m.is_none()) {
+            self.redo_stack.pop();
+        }
+        true
+    }
+
+    /// Moves backwards or forwards through the history so that `ply`
+    /// plies have been played, counting skips. A move and the skips after
+    /// it stay together, so a `ply` between them goes past the skips.
+    ///
+    /// Returns false if `ply` is past the end of the history and the plies
+    /// that could be redone.
+    pub fn jump_to_ply(&mut self, ply: usize) -> bool {
+        if ply > self.history.len() + self.redo_stack.len() {
+            return false;
+        }
+
+        while self.history.len() > ply {
+            let undone = self.history.pop().expect("history is longer than ply");
+            self.redo_stack.push(undone);
+        }
+        while self.history.len() < ply {
+            let redone = self.redo_stack.pop().expect("ply is within the redo stack");
+            self.history.push(redone);
+        }
+        while self.redo_stack.last().is_some_and(|ply| ply.m.is_none()) {
+            let skip = self.redo_stack.pop().expect("checked above");
+            self.history.push(skip);
+        }
+
+        self.replay();
+        true
+    }
+
+    /// The last move played, skipping over any skips after it
+    pub fn last_move(&self) -> Option<(i8, i8)> {
+        self.history.iter().rev().find_map(|ply| ply.m)
+    }
+
+    /// The squares flipped by the last move. Empty if no move has been
+    /// played.
+    pub fn last_move_flips(&self) -> Vec<(i8, i8)> {
+        let Some(last) = self.history.iter().rposition(|ply| ply.m.is_some()) else {
+            return Vec::new();
+        };
+
+        let mut board = self.start.clone();
+        for ply in &self.history[..last] {
+            if let Some((x, y)) = ply.m {
+                board.try_place_chip(x, y, ply.color);
+            }
+        }
+
+        let Ply { color, m } = self.history[last];
+        let (x, y) = m.expect("found a move");
+        board.flipped_squares(x, y, color)
+    }
+
+    /// The history in move notation, with "pass" for skips
+    pub fn history_notation(&self) -> Vec<String> {
+        self.history
+            .iter()
+            .map(|ply| match ply.m {
+                Some(m) => notation::format_move(m),
+                None => String::from("pass"),
+            })
+            .collect()
+    }
+
+    /// Plays a move and any skips that follow it, keeping the redo stack
+    fn apply(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        match self.turn {
+            None => return Err(OthelloError::GameOver),
+            Some(turn) if turn != color => return Err(OthelloError::WrongTurn),
+            Some(_) => {}
+        }
+
+        self.board.place_chip(x, y, color)?;
+
+        self.history.push(Ply {
+            color,
+            m: Some((x, y)),
+        });
+        self.turn = self.board.next_turn(color);
+
+        if self.turn == Some(color) {
+            self.history.push(Ply {
+                color: !color,
+                m: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the board and turn from the history
+    fn replay(&mut self) {
+        self.board = self.start.clone();
+        self.turn = self.start_turn;
+
+        for ply in &self.history {
+            if let Some((x, y)) = ply.m {
+                self.board.try_place_chip(x, y, ply.color);
+                self.turn = self.board.next_turn(ply.color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_record(game: &mut Game, record: &str) {
+        for m in notation::parse_moves(record).unwrap() {
+            let color = game.turn().unwrap();
+            game.play(m.0, m.1, color).unwrap();
+        }
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut game = Game::new();
+        assert!(!game.undo());
+        assert_eq!(game.play(5, 4, true), Err(OthelloError::WrongTurn));
+        assert_eq!(game.play(8, 4, false), Err(OthelloError::OutOfBounds));
+        assert!(game.history().is_empty());
+
+        play_record(&mut game, "f5d6c3");
+        let after_c3 = game.board().clone();
+
+        assert!(game.undo());
+        assert_eq!(game.history_notation(), vec!["f5", "d6"]);
+        assert_eq!(game.turn(), Some(false));
+
+        assert!(game.redo());
+        assert_eq!(game.board(), &after_c3);
+        assert!(!game.redo());
+
+        // a new move clears what could be redone
+        assert!(game.undo());
+        let m = *game.board().get_all_valid_moves(false).last().unwrap();
+        assert_ne!(m, (2, 2));
+        game.play(m.0, m.1, false).unwrap();
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn jump_to_ply() {
+        let mut game = Game::new();
+        play_record(&mut game, "f5d6c3d3c4");
+
+        assert!(game.jump_to_ply(0));
+        assert_eq!(game.board(), &Board::new());
+        assert_eq!(game.turn(), Some(false));
+
+        assert!(game.jump_to_ply(5));
+        assert_eq!(game.history().len(), 5);
+        assert!(!game.jump_to_ply(6));
+
+        assert!(game.jump_to_ply(2));
+        assert_eq!(game.history_notation(), vec!["f5", "d6"]);
+        assert_eq!(game.turn(), Some(false));
+    }
+
+    #[test]
+    fn skips_are_undone_with_the_move_before() {
+        // after white plays a3, black has no valid moves and skips
+        let mut game = Game::new();
+        play_record(&mut game, "c4c3e6b4a4a5c2a3");
+        assert_eq!(game.history().len(), 9);
+        assert_eq!(
+            game.history()[8],
+            Ply {
+                color: false,
+                m: None
+            }
+        );
+        assert_eq!(game.turn(), Some(true));
+        assert_eq!(game.history_notation().last().unwrap(), "pass");
+
+        assert!(game.undo());
+        assert_eq!(game.history().len(), 7);
+        assert_eq!(game.turn(), Some(true));
+
+        assert!(game.redo());
+        assert_eq!(game.history().len(), 9);
+        assert_eq!(game.turn(), Some(true));
+    }
+
+    #[test]
+    fn jumps_keep_skips_with_their_move() {
+        let mut game = Game::new();
+        play_record(&mut game, "c4c3e6b4a4a5c2a3");
+        let after_a3 = game.board().clone();
+
+        // ply 8 is between a3 and black's skip
+        assert!(game.jump_to_ply(8));
+        assert_eq!(game.history().len(), 9);
+        assert!(!game.redo());
+        assert_eq!(game.board(), &after_a3);
+
+        assert!(game.jump_to_ply(0));
+        assert!(game.jump_to_ply(8));
+        assert_eq!(game.history().len(), 9);
+        assert_eq!(game.turn(), Some(true));
+
+        // white moves again after the skip
+        let m = game.board().get_all_valid_moves(true)[0];
+        game.play(m.0, m.1, true).unwrap();
+        assert!(game.history()[9].color);
+
+        assert!(game.jump_to_ply(7));
+        assert!(game.redo());
+        assert_eq!(game.history().len(), 9);
+        assert_eq!(game.turn(), Some(true));
+    }
+
+    #[test]
+    fn last_move() {
+        let mut game = Game::new();
+        assert_eq!(game.last_move(), None);
+        assert!(game.last_move_flips().is_empty());
+
+        play_record(&mut game, "f5");
+        assert_eq!(game.last_move(), Some((5, 4)));
+        // f5 flips e5
+        assert_eq!(game.last_move_flips(), vec![(4, 4)]);
+
+        // the skip after a3 is passed over
+        let mut game = Game::new();
+        play_record(&mut game, "c4c3e6b4a4a5c2a3");
+        assert_eq!(game.last_move(), Some((0, 2)));
+        assert!(!game.last_move_flips().is_empty());
+    }
+
+    #[test]
+    fn custom_start() {
+        let mut start = Board::new();
+        crate::setup::add_handicap(&mut start, false, 1).unwrap();
+        let mut game = Game::from_start(start.clone(), true, Variant::Standard);
+        assert_eq!(game.turn(), Some(true));
+        assert_eq!(game.start_turn(), Some(true));
+
+        play_record(&mut game, "f4f3");
+        assert!(game.history()[0].color);
+        assert!(!game.history()[1].color);
+        assert!(game.jump_to_ply(0));
+        assert_eq!(game.board(), &start);
+        assert_eq!(game.turn(), Some(true));
+    }
+
+    #[test]
+    fn game_end() {
+        let mut game = Game::new();
+        play_record(&mut game, "f5f6c4c5f7b3b5e6a2");
+        assert_eq!(game.turn(), None);
+        assert_eq!(game.play(0, 0, false), Err(OthelloError::GameOver));
+    }
+}
@@ -0,0 +1,295 @@
+//! A line based command protocol in the style of the Go Text Protocol, so
+//! that scripts and test harnesses can play against any bot through stdin
+//! and stdout, see `src/bin/gtp.rs`.
+//!
+//! Every command gets a response starting with '=' on success or '?' on
+//! failure, followed by an empty line. A command may start with a number,
+//! which is repeated in its response, ex. "3 genmove w" -> "=3 d3".
+//!
+//! Supported commands: `protocol_version`, `name`, `version`,
+//! `known_command`, `list_commands`, `boardsize`, `clear_board`, `play`,
+//! `genmove`, `undo`, `showboard`, `final_score` and `quit`.
+//! Colors are written "b", "w", "black" or "white", and moves as in
+//! `notation`, ex. "f5", or "pass".
+
+use std::io::{self, Write};
+
+use crate::{
+    bots::MakeMove,
+    game::Game,
+    notation,
+    position::{AnyBoard, Position},
+};
+
+/// The name the engine gives for `name`
+pub const ENGINE_NAME: &str = "othello-rs";
+/// The protocol version the engine follows
+pub const PROTOCOL_VERSION: &str = "2";
+
+const COMMANDS: [&str; 13] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "boardsize",
+    "clear_board",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "final_score",
+    "quit",
+];
+
+pub struct Engine {
+    /// The bot that chooses moves for `genmove`
+    bot: Box<dyn MakeMove>,
+    game: Game<AnyBoard>,
+}
+
+impl Engine {
+    /// An engine on an 8x8 board, playing moves chosen by `bot`
+    pub fn new(bot: Box<dyn MakeMove>) -> Engine {
+        Engine {
+            bot,
+            game: Game::from_start(
+                AnyBoard::new(8).expect("8x8 is supported"),
+                false,
+                Default::default(),
+            ),
+        }
+    }
+
+    /// The game being played
+    pub fn game(&self) -> &Game<AnyBoard> {
+        &self.game
+    }
+
+    /// Handles a command, writing its response to `out`. Empty lines and
+    /// comments starting with '#' get no response.
+    ///
+    /// Returns false if the engine should quit.
+    pub fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace().peekable();
+        let Some(first) = words.peek() else {
+            return Ok(true);
+        };
+
+        let id = first.parse::<u32>().ok();
+        if id.is_some() {
+            words.next();
+        }
+        let id = id.map(|id| id.to_string()).unwrap_or_default();
+        let command = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+
+        match self.run(command, &args) {
+            Ok(response) if response.contains('\n') => write!(out, "={id}\n{response}\n\n")?,
+            Ok(response) if response.is_empty() => write!(out, "={id}\n\n")?,
+            Ok(response) => write!(out, "={id} {response}\n\n")?,
+            Err(err) => write!(out, "?{id} {err}\n\n")?,
+        }
+        Ok(command != "quit")
+    }
+
+    fn run(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok(PROTOCOL_VERSION.to_string()),
+            "name" => Ok(ENGINE_NAME.to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => Ok(COMMANDS.contains(&arg(args, 0)?).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "boardsize" => {
+                let size = arg(args, 0)?
+                    .parse()
+                    .map_err(|_| "boardsize not an integer")?;
+                let board = AnyBoard::new(size).map_err(|err| err.to_string())?;
+                self.game = Game::from_start(board, false, self.game.variant());
+                Ok(String::new())
+            }
+            "clear_board" => {
+                self.game = self.game.restarted();
+                Ok(String::new())
+            }
+            "play" => {
+                let color = parse_color(arg(args, 0)?)?;
+                self.play(color, arg(args, 1)?).map(|()| String::new())
+            }
+            "genmove" => self.genmove(parse_color(arg(args, 0)?)?),
+            "undo" => match self.game.undo() {
+                true => Ok(String::new()),
+                false => Err("cannot undo".to_string()),
+            },
+            "showboard" => Ok(format!("{:?}", self.game.board()).trim_end().to_string()),
+            "final_score" => Ok(self.final_score()),
+            "quit" => Ok(String::new()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    /// Plays a move for `color`. A pass is only allowed when `color` has no
+    /// valid moves, which the game has already skipped.
+    fn play(&mut self, color: bool, m: &str) -> Result<(), String> {
+        if m.eq_ignore_ascii_case("pass") {
+            return match self.game.board().has_valid_move(color) {
+                true => Err("illegal move: pass".to_string()),
+                false => Ok(()),
+            };
+        }
+
+        let board = self.game.board();
+        let (x, y) = notation::parse_move_sized(&m.to_ascii_lowercase(), board.size())
+            .ok_or_else(|| format!("invalid coordinate: {m}"))?;
+        self.game
+            .play(x, y, color)
+            .map_err(|err| format!("illegal move: {m} ({err})"))
+    }
+
+    /// Asks the bot for a move for `color` and plays it
+    fn genmove(&mut self, color: bool) -> Result<String, String> {
+        if !self.game.board().has_valid_move(color) {
+            return Ok("pass".to_string());
+        }
+
+        let (x, y) = self.game.board().ask_bot(self.bot.as_mut(), color);
+        self.game
+            .play(x, y, color)
+            .map_err(|err| format!("illegal move from the bot ({err})"))?;
+        Ok(notation::format_move((x, y)))
+    }
+
+    /// The winner and by how many pieces, ex. "B+4" or "W+2", or "0" for a
+    /// draw. Counts the pieces now if the game isn't over.
+    fn final_score(&self) -> String {
+        let board = self.game.board();
+        let margin = board.count_pieces(true).abs_diff(board.count_pieces(false));
+        match self.game.variant().winner(board) {
+            Some(true) => format!("W+{margin}"),
+            Some(false) => format!("B+{margin}"),
+            None => "0".to_string(),
+        }
+    }
+}
+
+/// The argument at `index`, or an error if the command has too few
+fn arg<'a>(args: &[&'a str], index: usize) -> Result<&'a str, String> {
+    args.get(index)
+        .copied()
+        .ok_or_else(|| "missing argument".to_string())
+}
+
+/// "b", "black", "w" or "white", in any case. True is white.
+fn parse_color(color: &str) -> Result<bool, String> {
+    match color.to_ascii_lowercase().as_str() {
+        "b" | "black" => Ok(false),
+        "w" | "white" => Ok(true),
+        _ => Err(format!("invalid color: {color}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots;
+
+    /// Runs commands and returns the engine's responses without the empty
+    /// lines between them
+    fn run(engine: &mut Engine, commands: &[&str]) -> Vec<String> {
+        let mut out = Vec::new();
+        for command in commands {
+            assert!(engine.handle(command, &mut out).unwrap(), "{command}");
+        }
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn engine() -> Engine {
+        Engine::new(bots::create_bot("first_valid_bot").unwrap())
+    }
+
+    #[test]
+    fn ids_and_errors() {
+        let mut engine = engine();
+        assert_eq!(
+            run(
+                &mut engine,
+                &[
+                    "1 name",
+                    "",
+                    "# a comment",
+                    "known_command genmove",
+                    "2 fly",
+                    "undo"
+                ]
+            ),
+            vec![
+                "=1 othello-rs",
+                "= true",
+                "?2 unknown command",
+                "? cannot undo"
+            ]
+        );
+        assert!(!engine.handle("quit", &mut Vec::new()).unwrap());
+    }
+
+    #[test]
+    fn moves() {
+        let mut engine = engine();
+        let out = run(
+            &mut engine,
+            &[
+                "play b F5",
+                "play b e6",
+                "play w pass",
+                "genmove w",
+                "play w z9",
+            ],
+        );
+        assert_eq!(out[0], "=");
+        assert!(out[1].starts_with("? illegal move: e6"));
+        assert_eq!(out[2], "? illegal move: pass");
+        assert_eq!(out[3], "= f4");
+        assert_eq!(out[4], "? invalid coordinate: z9");
+        assert_eq!(engine.game().history_notation(), vec!["f5", "f4"]);
+
+        run(&mut engine, &["undo"]);
+        assert_eq!(engine.game().history_notation(), vec!["f5"]);
+        run(&mut engine, &["clear_board"]);
+        assert!(engine.game().history().is_empty());
+    }
+
+    #[test]
+    fn sizes_and_scores() {
+        let mut engine = engine();
+        assert_eq!(
+            run(&mut engine, &["boardsize 5"]),
+            vec!["? Boards can't be 5x5"]
+        );
+        run(&mut engine, &["boardsize 4"]);
+        assert_eq!(engine.game().board().size(), 4);
+
+        while engine.game().turn().is_some() {
+            let color = if engine.game().turn() == Some(true) {
+                "w"
+            } else {
+                "b"
+            };
+            run(&mut engine, &[format!("genmove {color}").as_str()]);
+        }
+        let board = engine.game().board();
+        let (whites, blacks) = (board.count_pieces(true), board.count_pieces(false));
+        let score = &run(&mut engine, &["final_score"])[0];
+        match whites.cmp(&blacks) {
+            std::cmp::Ordering::Greater => assert_eq!(*score, format!("= W+{}", whites - blacks)),
+            std::cmp::Ordering::Less => assert_eq!(*score, format!("= B+{}", blacks - whites)),
+            std::cmp::Ordering::Equal => assert_eq!(score, "= 0"),
+        }
+        assert_eq!(run(&mut engine, &["genmove b"]), vec!["= pass"]);
+    }
+}
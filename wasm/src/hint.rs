@@ -0,0 +1,216 @@
+//! Move recommendations for human players, with a short explanation built
+//! from the same terms the heuristic bots look at.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    bots::{MakeMove, deep_heuristic_bot},
+    notation,
+    position::Position,
+    variant::Variant,
+};
+
+/// Strongest hint available, in plies searched
+pub const MAX_STRENGTH: u16 = 6;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hint {
+    #[serde(rename = "move")]
+    pub m: (i8, i8),
+    /// The move in notation, ex. "f5"
+    pub notation: String,
+    pub reasons: Vec<Reason>,
+    /// The reasons as short phrases, ex. "takes corner"
+    pub explanation: Vec<String>,
+}
+
+/// Something good or bad about a move
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Reason {
+    TakesCorner,
+    /// Plays next to an empty corner, which often lets the opponent take it
+    GivesUpXSquare,
+    /// Leaves the player with the most moves compared to the opponent, out
+    /// of all the valid moves
+    MaximisesMobility,
+    FlipsDiscs {
+        count: u32,
+    },
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::TakesCorner => write!(f, "takes corner"),
+            Reason::GivesUpXSquare => write!(f, "gives up X-square"),
+            Reason::MaximisesMobility => write!(f, "maximises mobility"),
+            Reason::FlipsDiscs { count: 1 } => write!(f, "flips 1 disc"),
+            Reason::FlipsDiscs { count } => write!(f, "flips {count} discs"),
+        }
+    }
+}
+
+/// Recommends the move `bot` would play for `color`, telling the bot which
+/// variant is played first.
+///
+/// Returns None if `color` has no valid moves.
+pub fn hint<B: Position>(
+    board: &B,
+    color: bool,
+    bot: &mut dyn MakeMove,
+    variant: Variant,
+) -> Option<Hint> {
+    if !board.has_valid_move(color) {
+        return None;
+    }
+
+    bot.set_variant(variant);
+    let m = board.ask_bot(bot, color);
+    let reasons = explain(board, m, color);
+
+    Some(Hint {
+        m,
+        notation: notation::format_move(m),
+        explanation: reasons.iter().map(|reason| reason.to_string()).collect(),
+        reasons,
+    })
+}
+
+/// A deep heuristic bot searching `strength` plies deep, for hints of a
+/// given strength. Strength is clamped to 1..=`MAX_STRENGTH`.
+pub fn strength_bot(strength: u16) -> deep_heuristic_bot::Bot {
+    deep_heuristic_bot::Bot::with_config(deep_heuristic_bot::Config {
+        depth: strength.clamp(1, MAX_STRENGTH),
+    })
+}
+
+/// What is good or bad about a valid move
+pub fn explain<B: Position>(board: &B, m: (i8, i8), color: bool) -> Vec<Reason> {
+    let mut reasons = Vec::new();
+    let last = board.size() - 1;
+    // the corner on the same side as a square in the corner's quarter
+    let nearest = |i: i8| if i < board.size() / 2 { 0 } else { last };
+    let corner = (nearest(m.0), nearest(m.1));
+
+    if m == corner {
+        reasons.push(Reason::TakesCorner);
+    }
+
+    // the square diagonally next to the corner
+    let x_square = (corner.0.abs_diff(1) as i8, corner.1.abs_diff(1) as i8);
+    if m == x_square && !board.is_occupied(corner.0, corner.1) {
+        reasons.push(Reason::GivesUpXSquare);
+    }
+
+    let moves = board.get_all_valid_moves(color);
+    if moves.len() > 1 {
+        let best_mobility = moves
+            .iter()
+            .map(|other| mobility(board, *other, color))
+            .max()
+            .expect("there are valid moves");
+        if mobility(board, m, color) == best_mobility {
+            reasons.push(Reason::MaximisesMobility);
+        }
+    }
+
+    reasons.push(Reason::FlipsDiscs {
+        count: board.flipped_squares(m.0, m.1, color).len() as u32,
+    });
+    reasons
+}
+
+/// The player's valid moves minus the opponent's after playing a move
+fn mobility<B: Position>(board: &B, m: (i8, i8), color: bool) -> i32 {
+    let mut future = board.clone();
+    future.try_place_chip(m.0, m.1, color);
+    future.get_all_valid_moves(color).len() as i32 - future.get_all_valid_moves(!color).len() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::Board,
+        bots::{create_bot, first_valid_bot},
+        create_board,
+        sized_board::SizedBoard,
+    };
+
+    #[test]
+    fn starting_position() {
+        let hint = hint(
+            &Board::new(),
+            false,
+            &mut strength_bot(3),
+            Variant::Standard,
+        )
+        .unwrap();
+        assert!(Board::new().is_valid_move(hint.m.0, hint.m.1, false));
+        assert_eq!(hint.notation, notation::format_move(hint.m));
+        assert!(hint.reasons.contains(&Reason::FlipsDiscs { count: 1 }));
+        assert!(hint.explanation.contains(&"flips 1 disc".to_string()));
+    }
+
+    #[test]
+    fn corners_and_x_squares() {
+        let board = create_board!(
+            [_ O X _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ O _ _ _ _ _],
+            [_ _ _ X _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+            [_ _ _ _ _ _ _ _],
+        );
+
+        let corner = explain(&board, (0, 0), false);
+        assert!(corner.contains(&Reason::TakesCorner));
+        assert!(corner.contains(&Reason::FlipsDiscs { count: 1 }));
+
+        let x_square = explain(&board, (1, 1), false);
+        assert!(x_square.contains(&Reason::GivesUpXSquare));
+        assert!(!x_square.contains(&Reason::TakesCorner));
+
+        assert_eq!(
+            hint(&board, false, &mut strength_bot(1), Variant::Standard)
+                .unwrap()
+                .m,
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn corners_of_other_sizes() {
+        let mut board = SizedBoard::empty(6).unwrap();
+        board.set(3, 3, false);
+        board.set(2, 2, true);
+        board.set(4, 4, true);
+
+        assert!(explain(&board, (5, 5), false).contains(&Reason::TakesCorner));
+        assert!(explain(&board, (1, 1), false).contains(&Reason::GivesUpXSquare));
+        assert_eq!(
+            hint(&board, false, &mut strength_bot(2), Variant::Standard)
+                .unwrap()
+                .m,
+            (5, 5)
+        );
+    }
+
+    #[test]
+    fn hints_from_any_bot() {
+        let board = Board::new();
+        let mut bot = first_valid_bot::Bot::new();
+        let first = hint(&board, false, &mut bot, Variant::Standard).unwrap();
+        assert_eq!(first.m, board.get_all_valid_moves(false)[0]);
+
+        let mut bot = create_bot("level_1").unwrap();
+        let level = hint(&board, false, bot.as_mut(), Variant::AntiOthello).unwrap();
+        assert!(board.is_valid_move(level.m.0, level.m.1, false));
+    }
+}
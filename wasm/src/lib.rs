@@ -1,21 +1,51 @@
-mod board;
-mod bots;
+#![allow(clippy::new_without_default)]
+
+pub mod analysis;
+pub mod board;
+pub mod bots;
+pub mod clock;
+pub mod error;
+pub mod game;
+#[cfg(feature = "native")]
+pub mod gtp;
+pub mod hint;
 mod js_console;
 mod macros;
-
-use wasm_bindgen::prelude::wasm_bindgen;
+pub mod move_request;
+#[cfg(feature = "native")]
+pub mod nboard;
+pub mod notation;
+pub mod opening_book;
+pub mod position;
+pub mod search;
+#[cfg(feature = "native")]
+pub mod server;
+pub mod session;
+pub mod setup;
+pub mod sized_board;
+pub mod snapshot;
+pub mod symmetry;
+pub mod td_learning;
+pub mod tournament;
+pub mod variant;
+
+use wasm_bindgen::{JsError, JsValue, prelude::wasm_bindgen};
 
 use crate::{
-    board::Board,
-    bots::{BotRunner, MakeMove},
+    bots::{BOT_NAMES, level_bot},
+    clock::Clock,
+    error::OthelloError,
+    move_request::MoveRequest,
+    position::Position,
+    search::{Progress, SearchControl},
+    session::{GameConfig, Sessions},
 };
 
+/// Errors are thrown as JS `Error`s with a message. Methods taking a `game`
+/// id throw an error if there is no game with that id.
 #[wasm_bindgen]
 struct JsInterface {
-    bot_runner: Option<BotRunner>,
-    white_bot: Option<Box<dyn MakeMove>>,
-    black_bot: Option<Box<dyn MakeMove>>,
-    staged_bot: Option<Box<dyn MakeMove>>,
+    sessions: Sessions,
 }
 
 #[wasm_bindgen]
@@ -23,146 +53,295 @@ struct JsInterface {
 impl JsInterface {
     pub fn new() -> JsInterface {
         JsInterface {
-            bot_runner: None,
-            white_bot: None,
-            black_bot: None,
-            staged_bot: None,
+            sessions: Sessions::new(),
         }
     }
 
-    pub fn create_new_center_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::center_bot::Bot::new()));
-    }
-
-    pub fn create_deep_heuristic_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::deep_heuristic_bot::Bot::new()));
-    }
-
-    pub fn create_deep_negative_heuristic_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::deep_negative_heuristic_bot::Bot::new()));
-    }
-
-    pub fn create_deep_score_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::deep_score_bot::Bot::new()));
+    /// Names of the bots that can be set in a `GameConfig`
+    pub fn bot_names() -> Vec<String> {
+        BOT_NAMES.iter().map(|name| name.to_string()).collect()
     }
 
-    pub fn create_new_edge_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::edge_bot::Bot::new()));
+    /// The difficulty levels from 1 to 10 as `Level` objects ({ depth,
+    /// noise, blunderChance, elo }), easiest first. A level's bot is set with
+    /// `GameConfig.set_black_level` or `GameConfig.set_white_level`.
+    pub fn levels() -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&level_bot::LEVELS)?)
     }
 
-    pub fn create_new_edge_exclusive_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::edge_exclusive_bot::Bot::new()));
+    /// Asks a bot for its move in a position, without a game. Takes a
+    /// `MoveRequest` object and returns a `MoveResponse` object, both of
+    /// which can be sent to and from a Web Worker.
+    pub fn run_move_request(request: JsValue) -> Result<JsValue, JsError> {
+        let request: MoveRequest = serde_wasm_bindgen::from_value(request)?;
+        let response = move_request::run(&request, None)?;
+        Ok(serde_wasm_bindgen::to_value(&response)?)
     }
 
-    pub fn create_new_first_valid_move_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::first_valid_bot::Bot::new()));
+    /// Like `run_move_request`, calling `on_progress` with a `Progress`
+    /// object ({ depth, bestMove, nodes }) every so often while the bot
+    /// searches. The search stops and the best move so far is played if
+    /// `on_progress` returns true.
+    pub fn run_move_request_with_progress(
+        request: JsValue,
+        on_progress: js_sys::Function,
+    ) -> Result<JsValue, JsError> {
+        let request: MoveRequest = serde_wasm_bindgen::from_value(request)?;
+        let control = JsSearchControl {
+            callback: on_progress,
+        };
+        let response = move_request::run(&request, Some(Box::new(control)))?;
+        Ok(serde_wasm_bindgen::to_value(&response)?)
     }
 
-    pub fn create_new_last_valid_move_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::last_valid_bot::Bot::new()))
+    /// Starts a new game and returns its id
+    pub fn create_game(&mut self, config: &GameConfig) -> Result<u32, JsError> {
+        Ok(self.sessions.create(config)?)
     }
 
-    pub fn create_new_minmax_score_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::minmax_score_bot::Bot::new()));
+    /// Ends a game, freeing it. Returns false if there was no game with the id.
+    pub fn end_game(&mut self, game: u32) -> bool {
+        self.sessions.remove(game)
     }
 
-    pub fn create_new_random_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::random_bot::Bot::new()))
+    /// Everything needed to draw the game as a `Snapshot` object ({ size,
+    /// cells (2 for blocked squares), turn, blackCount, whiteCount, legalMoves, lastMove,
+    /// lastFlipped, blackPassed, whitePassed, gameOver, result })
+    pub fn get_snapshot(&self, game: u32) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(serde_wasm_bindgen::to_value(&snapshot::snapshot(
+            &runner.game,
+        ))?)
     }
 
-    pub fn create_shallow_score_bot(&mut self) {
-        self.staged_bot = Some(Box::new(bots::shallow_score_bot::Bot::new()))
+    /// The filled squares as a bitboard. Throws an error if the board isn't
+    /// 8x8.
+    pub fn get_board_filled(&self, game: u32) -> Result<u64, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.board().as_board()?.filled)
     }
 
-    pub fn set_bot_as_white(&mut self) {
-        self.white_bot = self.staged_bot.take();
+    /// The colors of the squares as a bitboard, set for white. Throws an
+    /// error if the board isn't 8x8.
+    pub fn get_board_color(&self, game: u32) -> Result<u64, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.board().as_board()?.color)
     }
 
-    pub fn set_bot_as_black(&mut self) {
-        self.black_bot = self.staged_bot.take();
+    /// The board size, ex. 8 for 8x8
+    pub fn get_board_size(&self, game: u32) -> Result<i8, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.board().size())
     }
 
-    pub fn create_game(&mut self) {
-        self.bot_runner = Some(BotRunner::new(self.black_bot.take(), self.white_bot.take()));
+    /// The position as size * size characters, for `MoveRequest`s
+    pub fn get_board_text(&self, game: u32) -> Result<String, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(notation::format_board(runner.game.board()))
     }
 
-    pub fn get_board_filled(&self) -> u64 {
-        let runner = self.get_runner();
-        runner.board.filled
+    /// Plays a move for a human player, stopping their clock. Throws an
+    /// error if it isn't `color`'s turn, the move is not valid or a player
+    /// has run out of time.
+    pub fn board_place(&mut self, game: u32, x: i8, y: i8, color: bool) -> Result<(), JsError> {
+        let runner = self.sessions.get_mut(game)?;
+        Ok(runner.play(x, y, color)?)
     }
 
-    pub fn get_board_color(&self) -> u64 {
-        let runner = self.get_runner();
-        runner.board.color
+    /// The valid moves for the player whose turn it is, as an array of
+    /// [x, y]. Empty if the game is over.
+    pub fn get_legal_moves(&self, game: u32) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        let moves = runner.game.turn().map_or(Vec::new(), |color| {
+            runner.game.board().get_all_valid_moves(color)
+        });
+        Ok(serde_wasm_bindgen::to_value(&moves)?)
     }
 
-    pub fn board_try_place(&mut self, x: i8, y: i8, color: bool) -> bool {
-        let runner = self.get_runner_mut();
-        runner.board.try_place_chip(x, y, color)
+    /// The pieces that playing at (x, y) would flip for the player whose turn
+    /// it is, as an array of [x, y]. Empty if the move is not valid or the
+    /// game is over.
+    pub fn get_flips(&self, game: u32, x: i8, y: i8) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        let board = runner.game.board();
+        if !board.is_on_board(x, y) {
+            return Err(OthelloError::OutOfBounds.into());
+        }
+        let flips = runner
+            .game
+            .turn()
+            .map_or(Vec::new(), |color| board.flipped_squares(x, y, color));
+        Ok(serde_wasm_bindgen::to_value(&flips)?)
     }
 
     /// Checks if there is a valid move for a player
-    pub fn board_has_valid_move(&self, color: bool) -> bool {
-        let runner = self.get_runner();
-        runner.board.has_valid_move(color)
+    pub fn board_has_valid_move(&self, game: u32, color: bool) -> Result<bool, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.board().has_valid_move(color))
     }
 
     /// Counts the number of pieces of a color
-    pub fn board_count_pieces(&self, color: bool) -> u16 {
-        let runner = self.get_runner();
-        runner.board.count_pieces(color)
+    pub fn board_count_pieces(&self, game: u32, color: bool) -> Result<u16, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.board().count_pieces(color))
     }
 
-    pub fn bot_run_to_end(&mut self) {
-        let runner = self.get_runner_mut();
-        runner.run_game_to_end();
+    /// Plays a game between two bots to the end. Throws an error if a bot
+    /// forfeits by making an invalid move.
+    pub fn bot_run_to_end(&mut self, game: u32) -> Result<(), JsError> {
+        let runner = self.sessions.get_mut(game)?;
+        Ok(runner.run_game_to_end()?)
     }
 
-    pub fn bot_run_to_end_times(&mut self, times: u32) -> Vec<u32> {
-        let runner = self.get_runner_mut();
+    /// Plays `times` games between two bots and returns the number of wins
+    /// for black and white. A bot that forfeits loses.
+    pub fn bot_run_to_end_times(&mut self, game: u32, times: u32) -> Result<Vec<u32>, JsError> {
+        let runner = self.sessions.get_mut(game)?;
 
         let mut white_wins = 0;
         let mut black_wins = 0;
 
         for _ in 0..times {
-            runner.board = Board::new();
-            runner.run_game_to_end();
-
-            // note: this is inaccurate -- run game to end should check if the bot forfeited.
-            // if a bot forfeits with more score, then we shouldn't count it a win for the bot.
-            let whites = runner.board.count_pieces(true);
-            let blacks = runner.board.count_pieces(false);
-            if whites > blacks {
-                white_wins += 1;
-            } else if whites < blacks {
-                black_wins += 1;
+            runner.restart();
+            let white_won = match runner.run_game_to_end() {
+                Err(OthelloError::NotABot) => return Err(OthelloError::NotABot.into()),
+                Err(OthelloError::OutOfTime(color)) => Some(!color),
+                // the bot whose turn it was forfeited
+                Err(_) => runner.game.turn().map(|color| !color),
+                Ok(()) => runner.game.variant().winner(runner.game.board()),
+            };
+
+            match white_won {
+                Some(true) => white_wins += 1,
+                Some(false) => black_wins += 1,
+                None => {}
             }
         }
 
-        vec![black_wins, white_wins]
+        Ok(vec![black_wins, white_wins])
     }
 
-    pub fn bot_run_white(&mut self) -> Option<Vec<i8>> {
-        let runner = self.get_runner_mut();
+    /// Runs the white bot's move and returns it as [x, y]. Throws an error
+    /// if it isn't white's turn or the bot made an invalid move.
+    pub fn bot_run_white(&mut self, game: u32) -> Result<Vec<i8>, JsError> {
+        let runner = self.sessions.get_mut(game)?;
         let m = runner.run_white_bot()?;
-        Some(vec![m.0, m.1])
+        Ok(vec![m.0, m.1])
     }
 
-    pub fn bot_run_black(&mut self) -> Option<Vec<i8>> {
-        let runner = self.get_runner_mut();
+    /// Runs the black bot's move and returns it as [x, y]. Throws an error
+    /// if it isn't black's turn or the bot made an invalid move.
+    pub fn bot_run_black(&mut self, game: u32) -> Result<Vec<i8>, JsError> {
+        let runner = self.sessions.get_mut(game)?;
         let m = runner.run_black_bot()?;
-        Some(vec![m.0, m.1])
+        Ok(vec![m.0, m.1])
+    }
+
+    /// The color that played first, which is white in handicap games.
+    /// Undefined if no one could move from the start.
+    pub fn get_start_turn(&self, game: u32) -> Result<Option<bool>, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.start_turn())
+    }
+
+    /// The color to play next: true = white; false = black.
+    /// Undefined if the game is over.
+    pub fn get_turn(&self, game: u32) -> Result<Option<bool>, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.turn())
+    }
+
+    /// Takes back the last move. When playing against a bot, the bot's
+    /// moves are also taken back so it's the human's turn again.
+    pub fn undo(&mut self, game: u32) -> Result<bool, JsError> {
+        let runner = self.sessions.get_mut(game)?;
+        Ok(runner.undo())
+    }
+
+    /// Plays the last move taken back again
+    pub fn redo(&mut self, game: u32) -> Result<bool, JsError> {
+        let runner = self.sessions.get_mut(game)?;
+        Ok(runner.redo())
+    }
+
+    /// Goes to the position after `ply` moves and skips have been played,
+    /// keeping later moves so they can be redone
+    pub fn jump_to_ply(&mut self, game: u32, ply: usize) -> Result<bool, JsError> {
+        let runner = self.sessions.get_mut(game)?;
+        Ok(runner.jump_to_ply(ply))
+    }
+
+    /// The clocks of a game with time controls as a `ClockState` object
+    /// ({ blackMs, whiteMs, running, flagged }), where `flagged` is the
+    /// color that ran out of time and lost. Undefined for games without
+    /// clocks.
+    pub fn get_clock(&self, game: u32) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(serde_wasm_bindgen::to_value(
+            &runner.clock().map(Clock::state),
+        )?)
+    }
+
+    /// How long the bot to move should think, planned from the time left on
+    /// its clock, to pass as a move request's `timeLimitMs`. Undefined for
+    /// games without clocks.
+    pub fn get_move_time_ms(&self, game: u32) -> Result<Option<u32>, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner
+            .planned_move_time()
+            .map(|time| time.as_millis() as u32))
     }
 
-    fn get_runner(&self) -> &BotRunner {
-        self.bot_runner
-            .as_ref()
-            .expect("Bot should be initialized first")
+    /// Recommends the move the bot named `bot` would play for `color`.
+    /// Returns a `Hint` object ({ move, notation, reasons, explanation }) or
+    /// undefined if `color` has no valid moves.
+    pub fn hint(&self, game: u32, color: bool, bot: &str) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        let mut bot =
+            bots::create_bot(bot).ok_or_else(|| OthelloError::UnknownBot(bot.to_string()))?;
+        let hint = hint::hint(
+            runner.game.board(),
+            color,
+            bot.as_mut(),
+            runner.game.variant(),
+        );
+        Ok(serde_wasm_bindgen::to_value(&hint)?)
     }
 
-    fn get_runner_mut(&mut self) -> &mut BotRunner {
-        self.bot_runner
-            .as_mut()
-            .expect("Bot should be initalized first")
+    /// Compares each move played so far with the best move found searching
+    /// `depth` plies deep (1 to 6). Returns a `Report` object ({ moves,
+    /// turningPoint, blackAccuracy, whiteAccuracy }), where each move's
+    /// `ply` is its index in `get_history`.
+    pub fn analyze_game(&self, game: u32, depth: u16) -> Result<JsValue, JsError> {
+        let runner = self.sessions.get(game)?;
+        let report = analysis::analyze_game(&runner.game, depth.clamp(1, hint::MAX_STRENGTH));
+        Ok(serde_wasm_bindgen::to_value(&report)?)
+    }
+
+    /// The moves played so far in notation, ex. "f5", or "pass" for a skip.
+    /// Players alternate, starting with `get_start_turn`.
+    pub fn get_history(&self, game: u32) -> Result<Vec<String>, JsError> {
+        let runner = self.sessions.get(game)?;
+        Ok(runner.game.history_notation())
+    }
+}
+
+/// Passes search progress to a JS function, which returns true to stop
+struct JsSearchControl {
+    callback: js_sys::Function,
+}
+
+impl SearchControl for JsSearchControl {
+    fn should_stop(&mut self, progress: &Progress) -> bool {
+        let progress = match serde_wasm_bindgen::to_value(progress) {
+            Ok(progress) => progress,
+            Err(_) => return false,
+        };
+
+        match self.callback.call1(&JsValue::NULL, &progress) {
+            Ok(stop) => stop.is_truthy(),
+            // stop instead of throwing the same error thousands of times
+            Err(_) => true,
+        }
     }
 }
@@ -0,0 +1,157 @@
+//! Self-contained requests for a bot's move, so that bots can think in a
+//! Web Worker without freezing the page.
+//!
+//! Requests and responses are plain data that can be sent with
+//! `postMessage`. A worker can't be interrupted while it runs a request, so
+//! a search is cancelled by terminating the worker, or cut short ahead of
+//! time with `time_limit_ms` or a `SearchControl`.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::{
+    bots, error::OthelloError, notation, position::Position, search::SearchControl,
+    variant::Variant,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveRequest {
+    /// The position, written as by `notation::format_board`. The board size
+    /// is found from the number of squares.
+    pub board: String,
+    /// The color to move: true = white; false = black
+    pub color: bool,
+    /// The bot to ask, one of `bots::BOT_NAMES`
+    pub bot: String,
+    /// How long the bot may think. Bots that search play the best move
+    /// found so far when time runs out.
+    #[serde(default)]
+    pub time_limit_ms: Option<u32>,
+    /// The rules the bot plays by, standard Othello if left out
+    #[serde(default)]
+    pub variant: Variant,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveResponse {
+    /// The move as [x, y]. None if the color to move has no valid moves and
+    /// has to skip.
+    #[serde(rename = "move")]
+    pub m: Option<(i8, i8)>,
+    /// Positions looked at, for bots that search
+    pub nodes: Option<u64>,
+    pub elapsed_ms: u32,
+}
+
+/// Asks a newly created bot for its move. `control` can watch and stop the
+/// bot's search.
+///
+/// Returns an error if the board or bot name can't be read, or the bot
+/// doesn't make a valid move.
+pub fn run(
+    request: &MoveRequest,
+    control: Option<Box<dyn SearchControl>>,
+) -> Result<MoveResponse, OthelloError> {
+    let board = notation::parse_any_board(&request.board).ok_or(OthelloError::InvalidBoard)?;
+    let mut bot =
+        bots::create_bot(&request.bot).ok_or(OthelloError::UnknownBot(request.bot.clone()))?;
+
+    if !board.has_valid_move(request.color) {
+        return Ok(MoveResponse {
+            m: None,
+            nodes: None,
+            elapsed_ms: 0,
+        });
+    }
+
+    bot.set_time_limit(
+        request
+            .time_limit_ms
+            .map(|ms| Duration::from_millis(u64::from(ms))),
+    );
+    bot.set_search_control(control);
+    bot.set_variant(request.variant);
+
+    let start = Instant::now();
+    let m = board.ask_bot(bot.as_mut(), request.color);
+    let elapsed_ms = start.elapsed().as_millis() as u32;
+
+    board.clone().place_chip(m.0, m.1, request.color)?;
+
+    Ok(MoveResponse {
+        m: Some(m),
+        nodes: bot.nodes_searched(),
+        elapsed_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, sized_board::SizedBoard};
+
+    fn request(board: &Board, bot: &str, time_limit_ms: Option<u32>) -> MoveRequest {
+        MoveRequest {
+            board: notation::format_board(board),
+            color: false,
+            bot: bot.to_string(),
+            time_limit_ms,
+            variant: Variant::Standard,
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = r#"{
+            "board": "---------------------------OX------XO---------------------------",
+            "color": false,
+            "bot": "deep_heuristic_bot"
+        }"#;
+        let parsed: MoveRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed, request(&Board::new(), "deep_heuristic_bot", None));
+
+        let response = run(&parsed, None).unwrap();
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"move\":[") && json.contains("\"elapsedMs\""));
+        assert_eq!(
+            serde_json::from_str::<MoveResponse>(&json).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn searches_stop_at_time_limit() {
+        for bot in ["deep_heuristic_bot", "deep_score_bot", "mcts_bot"] {
+            let response = run(&request(&Board::new(), bot, Some(0)), None).unwrap();
+            let m = response.m.unwrap();
+            assert!(Board::new().is_valid_move(m.0, m.1, false), "{bot}");
+        }
+    }
+
+    #[test]
+    fn skips_and_errors() {
+        let mut no_moves = Board::new();
+        no_moves.clear(3, 3);
+        no_moves.clear(4, 4);
+        let response = run(&request(&no_moves, "random_bot", None), None).unwrap();
+        assert_eq!(response.m, None);
+
+        let mut bad_board = request(&Board::new(), "random_bot", None);
+        bad_board.board.pop();
+        assert_eq!(run(&bad_board, None), Err(OthelloError::InvalidBoard));
+
+        let mut outside = request(&Board::new(), "random_bot", None);
+        outside.board = notation::format_board(&SizedBoard::new(6).unwrap());
+        let m = run(&outside, None).unwrap().m.unwrap();
+        assert!(m.0 < 6 && m.1 < 6);
+
+        assert_eq!(
+            run(&request(&Board::new(), "no_such_bot", None), None),
+            Err(OthelloError::UnknownBot("no_such_bot".to_string()))
+        );
+    }
+}
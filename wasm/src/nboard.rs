@@ -0,0 +1,343 @@
+//! The NBoard engine protocol, so that the bots can be used as engines in
+//! desktop Othello GUIs. The GUI writes commands to the engine one line at a
+//! time and reads the engine's responses, see `src/bin/nboard.rs`.
+//!
+//! Supported commands: `nboard`, `set depth`, `set game`, `set contempt`,
+//! `move`, `go`, `hint`, `learn`, `ping` and `quit`. Other commands are
+//! ignored, as the protocol asks.
+//!
+//! The engine doesn't play its own moves after `go`; the GUI sends them back
+//! with `move` like any other move.
+
+use std::io::{self, Write};
+
+use web_time::Instant;
+
+use crate::{
+    analysis,
+    board::Board,
+    bots::{self, MakeMove, deep_heuristic_bot},
+    error::OthelloError,
+    notation,
+    search::Search,
+    variant::Variant,
+};
+
+/// The name the engine gives the GUI
+pub const ENGINE_NAME: &str = "othello-rs";
+/// Plies searched until the GUI sets a depth
+pub const DEFAULT_DEPTH: u16 = 5;
+/// Deepest search allowed. GUIs ask for up to 60 plies, which would take
+/// far too long without an endgame solver.
+pub const MAX_DEPTH: u16 = 12;
+
+pub struct Engine {
+    /// The bot that chooses moves for `go`, one of `bots::BOT_NAMES`
+    bot_name: String,
+    depth: u16,
+    board: Board,
+    /// The color to move
+    color: bool,
+}
+
+impl Engine {
+    /// An engine playing moves chosen by the bot with the name.
+    /// Returns an error if there is no bot with that name.
+    pub fn new(bot_name: &str) -> Result<Engine, OthelloError> {
+        if bots::create_bot(bot_name).is_none() {
+            return Err(OthelloError::UnknownBot(bot_name.to_string()));
+        }
+
+        Ok(Engine {
+            bot_name: bot_name.to_string(),
+            depth: DEFAULT_DEPTH,
+            board: Board::new(),
+            color: false,
+        })
+    }
+
+    /// Handles a command from the GUI, writing any responses to `out`.
+    ///
+    /// Returns false if the engine should quit.
+    pub fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+
+        let result = match command {
+            "nboard" => writeln!(out, "set myname {ENGINE_NAME}").map(Ok),
+            "set" => self.set(args),
+            "move" => Ok(self.play(args)),
+            "go" => self.go(out),
+            "hint" => self.hint(args, out),
+            "learn" => writeln!(out, "learned").map(Ok),
+            "ping" => writeln!(out, "pong {args}").map(Ok),
+            "quit" => return Ok(false),
+            _ => Ok(Ok(())),
+        };
+
+        if let Err(err) = result? {
+            writeln!(out, "status Error: {err}")?;
+        }
+        Ok(true)
+    }
+
+    fn set(&mut self, args: &str) -> io::Result<Result<(), OthelloError>> {
+        let (setting, value) = args.split_once(' ').unwrap_or((args, ""));
+
+        Ok(match setting {
+            "depth" => match value.trim().parse::<u16>() {
+                Ok(depth) => {
+                    self.depth = depth.clamp(1, MAX_DEPTH);
+                    Ok(())
+                }
+                Err(_) => Err(OthelloError::InvalidRecord),
+            },
+            "game" => parse_ggf(value).map(|(board, color)| {
+                self.board = board;
+                self.color = color;
+            }),
+            // contempt and anything else don't change how the bots play
+            _ => Ok(()),
+        })
+    }
+
+    /// Plays a move for the color to move, ex. "F5/1.50/0.02" or "PA" for
+    /// a pass. Evaluations and times after the move are ignored.
+    fn play(&mut self, m: &str) -> Result<(), OthelloError> {
+        play_move(&mut self.board, self.color, m)?;
+        self.color = !self.color;
+        Ok(())
+    }
+
+    fn go(&mut self, out: &mut impl Write) -> io::Result<Result<(), OthelloError>> {
+        if !self.board.has_valid_move(self.color) {
+            writeln!(out, "=== PA")?;
+            return Ok(Ok(()));
+        }
+
+        let mut bot = self.create_bot();
+        let start = Instant::now();
+        let m = bot.make_move(&self.board, self.color);
+        let seconds = start.elapsed().as_secs_f32();
+
+        if !self.board.is_valid_move(m.0, m.1, self.color) {
+            return Ok(Err(OthelloError::NoFlips));
+        }
+        if let Some(nodes) = bot.nodes_searched() {
+            writeln!(out, "nodestats {nodes} {seconds:.3}")?;
+        }
+        writeln!(out, "=== {}//{seconds:.3}", format_move(m))?;
+        Ok(Ok(()))
+    }
+
+    /// Sends the best `count` moves with their evaluations for the
+    /// analysis panel
+    fn hint(&mut self, count: &str, out: &mut impl Write) -> io::Result<Result<(), OthelloError>> {
+        let Ok(count) = count.parse::<usize>() else {
+            return Ok(Err(OthelloError::InvalidRecord));
+        };
+
+        writeln!(out, "status Thinking")?;
+        let mut search = Search::new(None, None);
+        let ranked = analysis::rank_moves(
+            &self.board,
+            self.color,
+            self.depth,
+            Variant::Standard,
+            &mut search,
+        )
+        .expect("searches without a limit finish");
+        for (m, score) in ranked.into_iter().take(count) {
+            writeln!(out, "search {} {score:.2} 0 {}", format_move(m), self.depth)?;
+        }
+        writeln!(out, "status")?;
+        Ok(Ok(()))
+    }
+
+    fn create_bot(&self) -> Box<dyn MakeMove> {
+        if self.bot_name == "deep_heuristic_bot" {
+            return Box::new(deep_heuristic_bot::Bot::with_config(
+                deep_heuristic_bot::Config { depth: self.depth },
+            ));
+        }
+        bots::create_bot(&self.bot_name).expect("the bot name was checked in `new`")
+    }
+}
+
+/// NBoard writes moves in upper case, ex. "F5"
+fn format_move(m: (i8, i8)) -> String {
+    notation::format_move(m).to_uppercase()
+}
+
+/// Plays a move written by the GUI, ignoring anything after a '/'
+fn play_move(board: &mut Board, color: bool, m: &str) -> Result<(), OthelloError> {
+    let m = m.split('/').next().unwrap_or_default().trim();
+    if m.eq_ignore_ascii_case("PA") {
+        return Ok(());
+    }
+
+    let (x, y) = notation::parse_move(m).ok_or(OthelloError::InvalidRecord)?;
+    board.place_chip(x, y, color)
+}
+
+/// Reads the position and color to move from a game in GGF, ex.
+/// "(;GM[Othello]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[F5]W[F6];)".
+/// Only 8x8 games are supported.
+fn parse_ggf(game: &str) -> Result<(Board, bool), OthelloError> {
+    let mut board = None;
+    let mut color = false;
+
+    for (name, value) in ggf_properties(game) {
+        match name {
+            "BO" => {
+                let mut parts: Vec<&str> = value.split_whitespace().collect();
+                let (Some(side), true) = (parts.pop(), parts.len() > 1) else {
+                    return Err(OthelloError::InvalidBoard);
+                };
+                let size = parts.remove(0);
+                if size != "8" {
+                    let size = size.parse().map_err(|_| OthelloError::InvalidBoard)?;
+                    return Err(OthelloError::NotEightByEight(size));
+                }
+
+                let squares = parts.concat().replace('*', "X");
+                board = Some(notation::parse_board(&squares).ok_or(OthelloError::InvalidBoard)?);
+                color = match side {
+                    "*" => false,
+                    "O" => true,
+                    _ => return Err(OthelloError::InvalidBoard),
+                };
+            }
+            "B" | "W" => {
+                let board = board.as_mut().ok_or(OthelloError::InvalidBoard)?;
+                let mover = name == "W";
+                play_move(board, mover, value)?;
+                color = !mover;
+            }
+            _ => {}
+        }
+    }
+
+    board
+        .map(|board| (board, color))
+        .ok_or(OthelloError::InvalidBoard)
+}
+
+/// The properties of a GGF game in order, as (name, value), ex. ("B", "F5")
+fn ggf_properties(game: &str) -> Vec<(&str, &str)> {
+    let mut properties = Vec::new();
+    let mut rest = game;
+
+    while let Some(open) = rest.find('[') {
+        // names are ASCII, so the byte before one ends a character even if
+        // the text before it isn't ASCII
+        let name_start = rest.as_bytes()[..open]
+            .iter()
+            .rposition(|b| !b.is_ascii_uppercase())
+            .map_or(0, |i| i + 1);
+        let Some(close) = rest[open..].find(']') else {
+            break;
+        };
+
+        properties.push((&rest[name_start..open], &rest[open + 1..open + close]));
+        rest = &rest[open + close + 1..];
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = "(;GM[Othello]PC[NBoard]PB[black]PW[white]RE[?]TI[15:00]TY[8]\
+        BO[8 ---------------------------O*------*O--------------------------- *]\
+        B[F5]W[F6//0.01]B[E6/1.50/0.02];)";
+
+    #[test]
+    fn non_ascii_properties() {
+        assert_eq!(
+            ggf_properties("(;PB[Jörg]PW[山田]ÉB[F5]W[F6];)"),
+            vec![("PB", "Jörg"), ("PW", "山田"), ("B", "F5"), ("W", "F6")]
+        );
+    }
+
+    /// Runs commands and returns the engine's responses
+    fn run(engine: &mut Engine, commands: &[&str]) -> Vec<String> {
+        let mut out = Vec::new();
+        for command in commands {
+            assert!(engine.handle(command, &mut out).unwrap(), "{command}");
+        }
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn handshake() {
+        let mut engine = Engine::new("random_bot").unwrap();
+        assert_eq!(
+            run(
+                &mut engine,
+                &["nboard 2", "set contempt 0", "ping 1", "learn"]
+            ),
+            vec!["set myname othello-rs", "pong 1", "learned"]
+        );
+        assert!(!engine.handle("quit", &mut Vec::new()).unwrap());
+        assert!(Engine::new("no_such_bot").is_err());
+    }
+
+    #[test]
+    fn games_and_moves() {
+        let (board, color) = parse_ggf(GAME).unwrap();
+        assert!(color);
+        assert_eq!(board.count_pieces(false) + board.count_pieces(true), 7);
+        assert!(board.is_occupied(4, 5) && !board.get_color(4, 5));
+
+        let mut engine = Engine::new("first_valid_bot").unwrap();
+        let game = format!("set game {GAME}");
+        let out = run(&mut engine, &[game.as_str(), "go"]);
+        let m = out.last().unwrap().strip_prefix("=== ").unwrap();
+        let m = notation::parse_move(&m[..2]).unwrap();
+        assert_eq!(m, board.get_all_valid_moves(true)[0]);
+
+        let out = run(&mut engine, &["move f4", "move a1"]);
+        assert_eq!(out, vec!["status Error: The move doesn't flip any pieces"]);
+        assert!(engine.board.is_occupied(5, 3));
+        assert!(!engine.color);
+    }
+
+    #[test]
+    fn hints_and_depth() {
+        let mut engine = Engine::new("deep_heuristic_bot").unwrap();
+        run(&mut engine, &["set depth 60"]);
+        assert_eq!(engine.depth, MAX_DEPTH);
+
+        let out = run(&mut engine, &["set depth 3", "hint 3"]);
+        assert_eq!(out.len(), 5);
+        assert_eq!(out[0], "status Thinking");
+        assert!(out[1..4].iter().all(|line| line.starts_with("search ")));
+        assert!(out[1].ends_with(" 0 3"));
+        assert_eq!(out[4], "status");
+
+        let out = run(&mut engine, &["set depth 2", "go"]);
+        assert!(out[0].starts_with("nodestats "));
+        assert!(out[1].starts_with("=== "));
+    }
+
+    #[test]
+    fn passes() {
+        let mut board = Board::new();
+        board.clear(3, 3);
+        board.clear(4, 4);
+        let text = notation::format_board(&board).replace('X', "*");
+        let game = format!("set game (;GM[Othello]BO[8 {text} *];)");
+
+        let mut engine = Engine::new("random_bot").unwrap();
+        assert_eq!(run(&mut engine, &[game.as_str(), "go"]), vec!["=== PA"]);
+        run(&mut engine, &["move PA"]);
+        assert!(engine.color);
+    }
+}
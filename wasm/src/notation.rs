@@ -0,0 +1,228 @@
+//! Standard Othello move notation.
+//!
+//! Columns are lettered a-h from left to right and rows are numbered 1-8
+//! from top to bottom, so (0, 0) is "a1" and black's first moves are d3,
+//! c4, f5 and e6. Larger boards go on to column j and row 10.
+//!
+//! Positions are written as 64 characters row by row starting from a1,
+//! with 'X' for black, 'O' for white, '-' for an empty square and '#' for a
+//! blocked square. Other board sizes have size * size characters.
+
+use crate::{
+    board::Board,
+    position::{AnyBoard, Position},
+    sized_board::SizedBoard,
+};
+
+const LETTERS: &[u8] = b"abcdefghij";
+
+/// Formats a move, ex. (5, 4) -> "f5"
+pub fn format_move(m: (i8, i8)) -> String {
+    format!("{}{}", LETTERS[m.0 as usize] as char, m.1 + 1)
+}
+
+/// Parses a move, ex. "f5" -> (5, 4). Letters may be upper or lower case.
+pub fn parse_move(s: &str) -> Option<(i8, i8)> {
+    parse_move_sized(s, 8)
+}
+
+/// Parses a move on a board with `size` squares along each side, ex.
+/// "j10" -> (9, 9) on a 10x10 board
+pub fn parse_move_sized(s: &str, size: i8) -> Option<(i8, i8)> {
+    let (letter, rank) = s.split_at_checked(1)?;
+
+    let letter = letter.as_bytes()[0].to_ascii_lowercase();
+    let x = LETTERS[..size as usize].iter().position(|c| *c == letter)?;
+    if rank.starts_with('0') || !rank.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let rank: i8 = rank.parse().ok()?;
+    if !(1..=size).contains(&rank) {
+        return None;
+    }
+
+    Some((x as i8, rank - 1))
+}
+
+/// Parses a list of moves written one after another, ex. "f5d6c3".
+/// Whitespace between moves is ignored. Skips are not written.
+pub fn parse_moves(s: &str) -> Option<Vec<(i8, i8)>> {
+    parse_moves_sized(s, 8)
+}
+
+/// Parses a list of moves on a board with `size` squares along each side
+pub fn parse_moves_sized(s: &str, size: i8) -> Option<Vec<(i8, i8)>> {
+    let s: String = s.split_whitespace().collect();
+    if !s.is_ascii() {
+        return None;
+    }
+
+    // each move is a letter and the digits after it
+    let mut moves = Vec::new();
+    let mut start = 0;
+    for (i, c) in s.char_indices().skip(1) {
+        if c.is_ascii_alphabetic() {
+            moves.push(parse_move_sized(&s[start..i], size)?);
+            start = i;
+        }
+    }
+    if !s.is_empty() {
+        moves.push(parse_move_sized(&s[start..], size)?);
+    }
+    Some(moves)
+}
+
+/// Formats a list of moves written one after another, ex. "f5d6c3"
+pub fn format_moves(moves: &[(i8, i8)]) -> String {
+    moves.iter().map(|m| format_move(*m)).collect()
+}
+
+/// Formats a position, ex. "---------------------------OX------XO---------------------------"
+/// for the starting position
+pub fn format_board<B: Position>(board: &B) -> String {
+    let size = board.size();
+    let mut s = String::with_capacity(size as usize * size as usize);
+    for y in 0..size {
+        for x in 0..size {
+            s.push(if board.is_blocked(x, y) {
+                '#'
+            } else if !board.is_occupied(x, y) {
+                '-'
+            } else if board.get_color(x, y) {
+                'O'
+            } else {
+                'X'
+            });
+        }
+    }
+    s
+}
+
+/// Parses a position written by `format_board`. Letters may be upper or
+/// lower case, '_' and '.' are also read as empty squares and whitespace
+/// is ignored.
+pub fn parse_board(s: &str) -> Option<Board> {
+    let mut board = Board {
+        filled: 0,
+        color: 0,
+        blocked: 0,
+    };
+    read_squares(&mut board, s)?;
+    Some(board)
+}
+
+/// Parses a position of any supported size, found from the number of
+/// squares. 8x8 positions are read into the fast `Board`.
+pub fn parse_any_board(s: &str) -> Option<AnyBoard> {
+    let squares = s.chars().filter(|c| !c.is_whitespace()).count();
+    let size = squares.isqrt();
+    if size * size != squares || size == 8 {
+        return parse_board(s).map(AnyBoard::Standard);
+    }
+
+    let mut board = SizedBoard::empty(u8::try_from(size).ok()?).ok()?;
+    read_squares(&mut board, s)?;
+    Some(AnyBoard::Sized(board))
+}
+
+/// Sets the squares of an empty board from text. Returns None if the text
+/// isn't a position of the board's size.
+fn read_squares<B: Position>(board: &mut B, s: &str) -> Option<()> {
+    let size = board.size();
+    let mut squares = s.chars().filter(|c| !c.is_whitespace());
+    for y in 0..size {
+        for x in 0..size {
+            match squares.next()? {
+                'X' | 'x' => board.set(x, y, false),
+                'O' | 'o' => board.set(x, y, true),
+                '-' | '_' | '.' => {}
+                '#' => board.set_blocked(x, y),
+                _ => return None,
+            }
+        }
+    }
+
+    if squares.next().is_some() {
+        return None;
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(parse_move(&format_move((x, y))), Some((x, y)));
+            }
+        }
+
+        assert_eq!(format_move((0, 0)), "a1");
+        assert_eq!(format_move((7, 7)), "h8");
+        assert_eq!(parse_move("F5"), Some((5, 4)));
+    }
+
+    #[test]
+    fn invalid_moves() {
+        assert_eq!(parse_move("i1"), None);
+        assert_eq!(parse_move("a0"), None);
+        assert_eq!(parse_move("a9"), None);
+        assert_eq!(parse_move("a"), None);
+        assert_eq!(parse_move("a01"), None);
+        assert_eq!(parse_move("a+1"), None);
+        assert_eq!(parse_moves("f5d"), None);
+        assert_eq!(parse_moves("5d"), None);
+    }
+
+    #[test]
+    fn larger_boards() {
+        assert_eq!(format_move((9, 9)), "j10");
+        assert_eq!(parse_move_sized("J10", 10), Some((9, 9)));
+        assert_eq!(parse_move_sized("a11", 10), None);
+        assert_eq!(parse_move_sized("e5", 4), None);
+        assert_eq!(
+            parse_moves_sized("a10j1 c3", 10),
+            Some(vec![(0, 9), (9, 0), (2, 2)])
+        );
+
+        let start = SizedBoard::new(6).unwrap();
+        let text = format_board(&start);
+        assert_eq!(text, "--------------OX----XO--------------");
+        assert_eq!(parse_any_board(&text), Some(AnyBoard::Sized(start)));
+        assert_eq!(
+            parse_any_board(&format_board(&Board::new())),
+            Some(AnyBoard::Standard(Board::new()))
+        );
+        assert_eq!(parse_any_board(&"-".repeat(49)), None);
+    }
+
+    #[test]
+    fn boards() {
+        let start = "---------------------------OX------XO---------------------------";
+        assert_eq!(format_board(&Board::new()), start);
+        assert_eq!(parse_board(start), Some(Board::new()));
+
+        let mut board = Board::new();
+        board.try_place_chip(5, 4, false);
+        assert_eq!(parse_board(&format_board(&board)), Some(board));
+
+        assert_eq!(parse_board(&start[1..]), None);
+        assert_eq!(parse_board(&format!("{start}-")), None);
+        assert_eq!(parse_board(&start.replace('O', "W")), None);
+
+        let holes = start.replacen('-', "#", 1);
+        let board = parse_board(&holes).unwrap();
+        assert!(board.is_blocked(0, 0));
+        assert_eq!(format_board(&board), holes);
+    }
+
+    #[test]
+    fn move_lists() {
+        let moves = parse_moves("f5d6 c3").unwrap();
+        assert_eq!(moves, vec![(5, 4), (3, 5), (2, 2)]);
+        assert_eq!(format_moves(&moves), "f5d6c3");
+    }
+}
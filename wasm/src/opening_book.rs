@@ -0,0 +1,461 @@
+//! Opening book of positions seen in played games.
+//!
+//! Positions are stored in a canonical form: the smallest of the board's
+//! eight rotations and reflections, together with the side to move. This
+//! lets games that open with mirrored moves share the same statistics.
+
+use std::{collections::HashMap, fmt::Write as _};
+#[cfg(feature = "native")]
+use std::{fs, io, path::Path};
+
+use rand::Rng;
+
+use crate::{board::Board, bots::MakeMove, notation, symmetry::Symmetry};
+
+/// Number of moves from the start of a game that are stored by default
+pub const DEFAULT_MAX_PLIES: usize = 16;
+
+/// A canonical position and the side to move, which the book's moves are
+/// stored under
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct BookEntry {
+    filled: u64,
+    color: u64,
+    to_move: bool,
+}
+
+/// Statistics for a move in a book position.
+///
+/// Results are counted for the player making the move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookMove {
+    pub m: (i8, i8),
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+}
+
+impl BookMove {
+    /// Average result of the games with this move. 1 = always won, 0 = always lost
+    pub fn score(&self) -> f32 {
+        if self.games == 0 {
+            return 0f32;
+        }
+        (self.wins as f32 + self.draws as f32 * 0.5f32) / self.games as f32
+    }
+}
+
+pub struct OpeningBook {
+    positions: HashMap<BookEntry, Vec<BookMove>>,
+    max_plies: usize,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::with_max_plies(DEFAULT_MAX_PLIES)
+    }
+
+    /// Creates a book that only stores the first `max_plies` moves of each game
+    pub fn with_max_plies(max_plies: usize) -> OpeningBook {
+        OpeningBook {
+            positions: HashMap::new(),
+            max_plies,
+        }
+    }
+
+    /// Number of positions in the book
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Adds a game played from the starting position. Skips are not part of
+    /// the move list. The winner is decided by the pieces on the board after
+    /// the last move.
+    ///
+    /// Returns false, without changing the book, if a move is not valid.
+    pub fn add_game(&mut self, moves: &[(i8, i8)]) -> bool {
+        let mut board = Board::new();
+        let mut turn = Some(false);
+        let mut played = Vec::new();
+
+        for m in moves {
+            let color = match turn {
+                Some(color) => color,
+                None => return false,
+            };
+
+            let before = board.clone();
+            if !board.try_place_chip(m.0, m.1, color) {
+                return false;
+            }
+
+            played.push((before, color, *m));
+            turn = board.next_turn(color);
+        }
+
+        let blacks = board.count_pieces(false);
+        let whites = board.count_pieces(true);
+
+        for (before, color, m) in played.into_iter().take(self.max_plies) {
+            let (score, opponent_score) = if color {
+                (whites, blacks)
+            } else {
+                (blacks, whites)
+            };
+            self.record(&before, color, m, score.cmp(&opponent_score));
+        }
+
+        true
+    }
+
+    /// Adds a game in move list notation, ex. "f5d6c3d3c4".
+    ///
+    /// Returns false, without changing the book, if the game could not be read.
+    pub fn add_game_record(&mut self, record: &str) -> bool {
+        match notation::parse_moves(record) {
+            Some(moves) => self.add_game(&moves),
+            None => false,
+        }
+    }
+
+    /// Plays `games` games between two bots and adds them to the book.
+    /// Bots that always play the same moves will only add one line.
+    pub fn learn_from_self_play(
+        &mut self,
+        black_bot: &mut dyn MakeMove,
+        white_bot: &mut dyn MakeMove,
+        games: u32,
+    ) {
+        for _ in 0..games {
+            let mut board = Board::new();
+            let mut turn = Some(false);
+            let mut moves = Vec::new();
+
+            while let Some(color) = turn {
+                let bot: &mut dyn MakeMove = if color {
+                    &mut *white_bot
+                } else {
+                    &mut *black_bot
+                };
+
+                let m = bot.make_move(&board, color);
+                if board.place_chip(m.0, m.1, color).is_err() {
+                    // the bot forfeited; the game so far can still be learned from
+                    break;
+                }
+
+                moves.push(m);
+                turn = board.next_turn(color);
+            }
+
+            self.add_game(&moves);
+        }
+    }
+
+    /// Returns the book moves for a position, most played first.
+    /// Empty if the position is not in the book.
+    pub fn lookup(&self, board: &Board, color: bool) -> Vec<BookMove> {
+        let (position, symmetries) = canonical(board, color);
+
+        let mut moves: Vec<BookMove> = match self.positions.get(&position) {
+            Some(moves) => moves
+                .iter()
+                .map(|book_move| BookMove {
+                    m: symmetries[0].inverse().transform_move(book_move.m),
+                    ..book_move.clone()
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.games));
+        moves
+    }
+
+    /// Chooses a book move for a position.
+    ///
+    /// If `weighted_random` is true, moves are picked randomly, weighted by
+    /// how often they were played and how well they scored. Otherwise the
+    /// best scoring move is picked.
+    ///
+    /// Only moves played in at least `min_games` games are considered.
+    pub fn choose_move(
+        &self,
+        board: &Board,
+        color: bool,
+        min_games: u32,
+        weighted_random: bool,
+        rng: &mut impl Rng,
+    ) -> Option<(i8, i8)> {
+        let moves: Vec<BookMove> = self
+            .lookup(board, color)
+            .into_iter()
+            .filter(|book_move| book_move.games >= min_games.max(1))
+            .collect();
+
+        if !weighted_random {
+            return moves
+                .iter()
+                .max_by(|a, b| a.score().total_cmp(&b.score()))
+                .map(|book_move| book_move.m);
+        }
+
+        // moves that always lost can still be picked occasionally
+        let weights: Vec<f32> = moves
+            .iter()
+            .map(|book_move| book_move.games as f32 * (book_move.score() + 0.05f32))
+            .collect();
+        let total: f32 = weights.iter().sum();
+        if total <= 0f32 {
+            return None;
+        }
+
+        let mut target = rng.random_range(0f32..total);
+        for (book_move, weight) in moves.iter().zip(weights) {
+            if target < weight {
+                return Some(book_move.m);
+            }
+            target -= weight;
+        }
+
+        moves.last().map(|book_move| book_move.m)
+    }
+
+    /// Writes the book as text, one move of a position per line:
+    /// `<filled> <color> <b|w> <move> <games> <wins> <draws>`,
+    /// where filled and color are the board bits in hex.
+    pub fn to_text(&self) -> String {
+        let mut positions: Vec<_> = self.positions.iter().collect();
+        positions.sort_by_key(|(position, _)| (position.filled, position.color, position.to_move));
+
+        let mut text = format!("# othello opening book\nmax_plies {}\n", self.max_plies);
+        for (position, moves) in positions {
+            for book_move in moves {
+                writeln!(
+                    text,
+                    "{:016x} {:016x} {} {} {} {} {}",
+                    position.filled,
+                    position.color,
+                    if position.to_move { 'w' } else { 'b' },
+                    notation::format_move(book_move.m),
+                    book_move.games,
+                    book_move.wins,
+                    book_move.draws,
+                )
+                .expect("writing to a string does not fail");
+            }
+        }
+
+        text
+    }
+
+    /// Reads a book written by `to_text`. Returns None if the text is not a
+    /// valid book.
+    pub fn from_text(text: &str) -> Option<OpeningBook> {
+        let mut book = OpeningBook::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let ["max_plies", max_plies] = parts[..] {
+                book.max_plies = max_plies.parse().ok()?;
+                continue;
+            }
+
+            let [filled, color, to_move, m, games, wins, draws] = parts[..] else {
+                return None;
+            };
+
+            let position = BookEntry {
+                filled: u64::from_str_radix(filled, 16).ok()?,
+                color: u64::from_str_radix(color, 16).ok()?,
+                to_move: match to_move {
+                    "b" => false,
+                    "w" => true,
+                    _ => return None,
+                },
+            };
+
+            book.positions.entry(position).or_default().push(BookMove {
+                m: notation::parse_move(m)?,
+                games: games.parse().ok()?,
+                wins: wins.parse().ok()?,
+                draws: draws.parse().ok()?,
+            });
+        }
+
+        Some(book)
+    }
+
+    #[cfg(feature = "native")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    #[cfg(feature = "native")]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<OpeningBook> {
+        let text = fs::read_to_string(path)?;
+        OpeningBook::from_text(&text)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid opening book"))
+    }
+
+    fn record(&mut self, board: &Board, color: bool, m: (i8, i8), result: std::cmp::Ordering) {
+        // moves that are mirror images of each other on a symmetric board are the same move
+        let (position, symmetries) = canonical(board, color);
+        let m = symmetries
+            .iter()
+            .map(|symmetry| symmetry.transform_move(m))
+            .min()
+            .expect("at least one symmetry maps onto the canonical position");
+
+        let moves = self.positions.entry(position).or_default();
+        let index = match moves.iter().position(|book_move| book_move.m == m) {
+            Some(index) => index,
+            None => {
+                moves.push(BookMove {
+                    m,
+                    games: 0,
+                    wins: 0,
+                    draws: 0,
+                });
+                moves.len() - 1
+            }
+        };
+
+        let book_move = &mut moves[index];
+        book_move.games += 1;
+        match result {
+            std::cmp::Ordering::Greater => book_move.wins += 1,
+            std::cmp::Ordering::Equal => book_move.draws += 1,
+            std::cmp::Ordering::Less => {}
+        }
+    }
+}
+
+/// Finds the canonical position of a board and the symmetries that map the
+/// board onto it. Symmetric boards, like the starting board, are mapped onto
+/// the canonical position by more than one symmetry.
+fn canonical(board: &Board, color: bool) -> (BookEntry, Vec<Symmetry>) {
+    let (canonical, _) = board.canonical();
+    let symmetries = Symmetry::ALL
+        .into_iter()
+        .filter(|symmetry| board.transform(*symmetry) == canonical)
+        .collect();
+
+    let position = BookEntry {
+        filled: canonical.filled,
+        color: canonical.color,
+        to_move: color,
+    };
+    (position, symmetries)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::bots::random_bot;
+
+    #[test]
+    fn symmetric_openings_share_positions() {
+        let mut book = OpeningBook::new();
+
+        // the four opening moves for black are all symmetric
+        assert!(book.add_game_record("f5"));
+        assert!(book.add_game_record("d3"));
+        assert!(book.add_game_record("c4"));
+        assert!(book.add_game_record("e6"));
+        assert_eq!(book.len(), 1);
+
+        let moves = book.lookup(&Board::new(), false);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].games, 4);
+
+        // the stored move is mapped back onto the board it is looked up on
+        let valid_moves = Board::new().get_all_valid_moves(false);
+        assert!(valid_moves.contains(&moves[0].m));
+    }
+
+    #[test]
+    fn rejects_invalid_games() {
+        let mut book = OpeningBook::new();
+        assert!(!book.add_game_record("a1"));
+        assert!(!book.add_game_record("f5f5"));
+        assert!(!book.add_game_record("not a game"));
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn counts_results() {
+        let mut book = OpeningBook::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+        // after f5 d6 c3, black has more pieces; after f5 f6, pieces are tied
+        book.add_game_record("f5d6c3");
+        book.add_game_record("f5f6");
+
+        let mut board = Board::new();
+        board.try_place_chip(5, 4, false);
+
+        let moves = book.lookup(&board, true);
+        assert_eq!(moves.len(), 2);
+        let d6 = moves.iter().find(|m| m.m == (3, 5)).unwrap();
+        assert_eq!((d6.games, d6.wins, d6.draws), (1, 0, 0));
+        let f6 = moves.iter().find(|m| m.m == (5, 5)).unwrap();
+        assert_eq!((f6.games, f6.wins, f6.draws), (1, 0, 1));
+
+        assert_eq!(
+            book.choose_move(&board, true, 1, false, &mut rng),
+            Some((5, 5))
+        );
+        assert_eq!(book.choose_move(&board, true, 2, false, &mut rng), None);
+    }
+
+    #[test]
+    fn learns_from_self_play() {
+        let mut book = OpeningBook::with_max_plies(6);
+        let mut black_bot = random_bot::Bot::new();
+        let mut white_bot = random_bot::Bot::new();
+        book.learn_from_self_play(&mut black_bot, &mut white_bot, 20);
+
+        let moves = book.lookup(&Board::new(), false);
+        assert_eq!(moves.iter().map(|m| m.games).sum::<u32>(), 20);
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut book = OpeningBook::with_max_plies(4);
+        book.add_game_record("f5d6c3d3c4f4");
+        book.add_game_record("f5f6e6f4");
+
+        let loaded = OpeningBook::from_text(&book.to_text()).unwrap();
+        assert_eq!(loaded.max_plies, 4);
+        assert_eq!(loaded.positions, book.positions);
+        assert_eq!(loaded.to_text(), book.to_text());
+
+        assert!(OpeningBook::from_text("0 0 x f5 1 1 0").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn save_and_load() {
+        let mut book = OpeningBook::with_max_plies(4);
+        book.add_game_record("f5d6c3d3c4f4");
+
+        let path = std::env::temp_dir().join(format!("opening_book_{}.txt", std::process::id()));
+        book.save(&path).unwrap();
+        assert_eq!(OpeningBook::load(&path).unwrap().to_text(), book.to_text());
+        fs::remove_file(&path).unwrap();
+
+        assert!(OpeningBook::load(&path).is_err());
+    }
+}
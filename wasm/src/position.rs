@@ -0,0 +1,357 @@
+//! The rules of the game for boards of any size. `Board` is the fast 8x8
+//! case, `SizedBoard` covers the other sizes and `AnyBoard` holds either,
+//! for games whose size is chosen at run time.
+
+use std::fmt::Debug;
+
+use crate::{board::Board, bots::MakeMove, error::OthelloError, sized_board::SizedBoard};
+
+static DIRECTIONS: &[(i8, i8)] = &[
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A square board of pieces. Colors: true = white; false = black
+pub trait Position: Clone + Debug + PartialEq {
+    /// Squares along each side
+    fn size(&self) -> i8;
+
+    /// Precondition: the square is on the board
+    fn is_occupied(&self, x: i8, y: i8) -> bool;
+
+    /// Precondition: the square is on the board
+    fn get_color(&self, x: i8, y: i8) -> bool;
+
+    /// Puts a piece on a square without flipping any others.
+    ///
+    /// Precondition: the square is on the board
+    fn set(&mut self, x: i8, y: i8, color: bool);
+
+    /// True if no piece can be placed on the square.
+    ///
+    /// Precondition: the square is on the board
+    fn is_blocked(&self, x: i8, y: i8) -> bool;
+
+    /// Blocks an empty square for the rest of the game.
+    ///
+    /// Precondition: the square is on the board
+    fn set_blocked(&mut self, x: i8, y: i8);
+
+    /// Places a piece and flips the pieces it captures.
+    ///
+    /// Precondition: the square is on the board
+    ///
+    /// Returns true if the move was valid.
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool;
+
+    /// Precondition: the square is on the board
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool;
+
+    fn count_pieces(&self, color: bool) -> u16;
+
+    /// Asks a bot for its move in this position
+    fn ask_bot(&self, bot: &mut dyn MakeMove, color: bool) -> (i8, i8);
+
+    fn is_on_board(&self, x: i8, y: i8) -> bool {
+        (0..self.size()).contains(&x) && (0..self.size()).contains(&y)
+    }
+
+    /// Places a piece like `try_place_chip`, checking the coordinates first
+    /// and returning why the move failed
+    fn place_chip(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        if !self.is_on_board(x, y) {
+            return Err(OthelloError::OutOfBounds);
+        }
+        if self.is_occupied(x, y) {
+            return Err(OthelloError::Occupied);
+        }
+        if self.is_blocked(x, y) {
+            return Err(OthelloError::Blocked);
+        }
+        if !self.try_place_chip(x, y, color) {
+            return Err(OthelloError::NoFlips);
+        }
+        Ok(())
+    }
+
+    /// All valid moves for a color, row by row from the top left
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)> {
+        let size = self.size();
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|(x, y)| self.is_valid_move(*x, *y, color))
+            .collect()
+    }
+
+    fn has_valid_move(&self, color: bool) -> bool {
+        let size = self.size();
+        (0..size).any(|y| (0..size).any(|x| self.is_valid_move(x, y, color)))
+    }
+
+    /// Determines who plays after `color` has moved. A player with no valid
+    /// moves skips, and the game ends when neither player can move.
+    ///
+    /// Returns None if the game is over.
+    fn next_turn(&self, color: bool) -> Option<bool> {
+        if self.has_valid_move(!color) {
+            Some(!color)
+        } else if self.has_valid_move(color) {
+            Some(color)
+        } else {
+            None
+        }
+    }
+
+    /// The squares a move would flip. Empty if the move is not valid.
+    fn flipped_squares(&self, x: i8, y: i8, color: bool) -> Vec<(i8, i8)> {
+        let mut future = self.clone();
+        if !self.is_on_board(x, y) || !future.try_place_chip(x, y, color) {
+            return Vec::new();
+        }
+
+        let size = self.size();
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                self.is_occupied(*x, *y) && self.get_color(*x, *y) != future.get_color(*x, *y)
+            })
+            .collect()
+    }
+}
+
+/// Walks from a move in each direction and calls `flip` with the squares
+/// each capturing line would flip. Returns true if anything was captured.
+///
+/// Shared by the boards that don't use bitboard tricks.
+pub(crate) fn capture_lines<P: Position>(
+    board: &P,
+    x: i8,
+    y: i8,
+    color: bool,
+    mut flip: impl FnMut(i8, i8),
+) -> bool {
+    if board.is_occupied(x, y) || board.is_blocked(x, y) {
+        return false;
+    }
+
+    let mut captured = false;
+    for (dx, dy) in DIRECTIONS {
+        let (mut curr_x, mut curr_y) = (x + dx, y + dy);
+        let mut length = 0;
+
+        while board.is_on_board(curr_x, curr_y)
+            && board.is_occupied(curr_x, curr_y)
+            && board.get_color(curr_x, curr_y) != color
+        {
+            curr_x += dx;
+            curr_y += dy;
+            length += 1;
+        }
+
+        if length > 0
+            && board.is_on_board(curr_x, curr_y)
+            && board.is_occupied(curr_x, curr_y)
+            && board.get_color(curr_x, curr_y) == color
+        {
+            captured = true;
+            for i in 1..=length {
+                flip(x + dx * i, y + dy * i);
+            }
+        }
+    }
+    captured
+}
+
+impl Position for Board {
+    fn size(&self) -> i8 {
+        8
+    }
+
+    fn is_occupied(&self, x: i8, y: i8) -> bool {
+        Board::is_occupied(self, x, y)
+    }
+
+    fn get_color(&self, x: i8, y: i8) -> bool {
+        Board::get_color(self, x, y)
+    }
+
+    fn set(&mut self, x: i8, y: i8, color: bool) {
+        Board::set(self, x, y, color)
+    }
+
+    fn is_blocked(&self, x: i8, y: i8) -> bool {
+        Board::is_blocked(self, x, y)
+    }
+
+    fn set_blocked(&mut self, x: i8, y: i8) {
+        Board::set_blocked(self, x, y)
+    }
+
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
+        Board::try_place_chip(self, x, y, color)
+    }
+
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
+        Board::is_valid_move(self, x, y, color)
+    }
+
+    fn count_pieces(&self, color: bool) -> u16 {
+        Board::count_pieces(self, color)
+    }
+
+    fn ask_bot(&self, bot: &mut dyn MakeMove, color: bool) -> (i8, i8) {
+        bot.make_move(self, color)
+    }
+
+    fn place_chip(&mut self, x: i8, y: i8, color: bool) -> Result<(), OthelloError> {
+        Board::place_chip(self, x, y, color)
+    }
+
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)> {
+        Board::get_all_valid_moves(self, color)
+    }
+
+    fn has_valid_move(&self, color: bool) -> bool {
+        Board::has_valid_move(self, color)
+    }
+
+    fn next_turn(&self, color: bool) -> Option<bool> {
+        Board::next_turn(self, color)
+    }
+}
+
+/// A board of a size chosen at run time, keeping the fast `Board` for 8x8
+#[derive(Clone, PartialEq)]
+pub enum AnyBoard {
+    Standard(Board),
+    Sized(SizedBoard),
+}
+
+impl AnyBoard {
+    /// The starting position for a board size.
+    /// Returns an error if the size isn't supported, see `SizedBoard::new`.
+    pub fn new(size: u8) -> Result<AnyBoard, OthelloError> {
+        if size == 8 {
+            Ok(AnyBoard::Standard(Board::new()))
+        } else {
+            SizedBoard::new(size).map(AnyBoard::Sized)
+        }
+    }
+
+    /// The 8x8 board, for code that only works on bitboards.
+    /// Returns an error for other sizes.
+    pub fn as_board(&self) -> Result<&Board, OthelloError> {
+        match self {
+            AnyBoard::Standard(board) => Ok(board),
+            AnyBoard::Sized(board) => Err(OthelloError::NotEightByEight(board.size() as u8)),
+        }
+    }
+}
+
+macro_rules! each_board {
+    ($board:expr, $inner:ident => $body:expr) => {
+        match $board {
+            AnyBoard::Standard($inner) => $body,
+            AnyBoard::Sized($inner) => $body,
+        }
+    };
+}
+
+/// Draws the board inside
+impl Debug for AnyBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        each_board!(self, board => board.fmt(f))
+    }
+}
+
+impl Position for AnyBoard {
+    fn size(&self) -> i8 {
+        each_board!(self, board => board.size())
+    }
+
+    fn is_occupied(&self, x: i8, y: i8) -> bool {
+        each_board!(self, board => Position::is_occupied(board, x, y))
+    }
+
+    fn get_color(&self, x: i8, y: i8) -> bool {
+        each_board!(self, board => Position::get_color(board, x, y))
+    }
+
+    fn set(&mut self, x: i8, y: i8, color: bool) {
+        each_board!(self, board => Position::set(board, x, y, color))
+    }
+
+    fn is_blocked(&self, x: i8, y: i8) -> bool {
+        each_board!(self, board => Position::is_blocked(board, x, y))
+    }
+
+    fn set_blocked(&mut self, x: i8, y: i8) {
+        each_board!(self, board => Position::set_blocked(board, x, y))
+    }
+
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
+        each_board!(self, board => Position::try_place_chip(board, x, y, color))
+    }
+
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
+        each_board!(self, board => Position::is_valid_move(board, x, y, color))
+    }
+
+    fn count_pieces(&self, color: bool) -> u16 {
+        each_board!(self, board => Position::count_pieces(board, color))
+    }
+
+    fn ask_bot(&self, bot: &mut dyn MakeMove, color: bool) -> (i8, i8) {
+        each_board!(self, board => board.ask_bot(bot, color))
+    }
+
+    fn get_all_valid_moves(&self, color: bool) -> Vec<(i8, i8)> {
+        each_board!(self, board => Position::get_all_valid_moves(board, color))
+    }
+
+    fn has_valid_move(&self, color: bool) -> bool {
+        each_board!(self, board => Position::has_valid_move(board, color))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_board_matches_board() {
+        let mut board = Board::new();
+        let mut any = AnyBoard::new(8).unwrap();
+        assert_eq!(any, AnyBoard::Standard(Board::new()));
+
+        let mut color = false;
+        for _ in 0..10 {
+            let (x, y) = board.get_all_valid_moves(color)[0];
+            assert_eq!(
+                Position::get_all_valid_moves(&any, color),
+                board.get_all_valid_moves(color)
+            );
+            assert_eq!(
+                any.flipped_squares(x, y, color).len() as u32,
+                board.flipped_by(x, y, color).count_ones()
+            );
+            board.place_chip(x, y, color).unwrap();
+            any.place_chip(x, y, color).unwrap();
+            color = board.next_turn(color).unwrap();
+        }
+        assert_eq!(any, AnyBoard::Standard(board));
+
+        assert_eq!(any.place_chip(8, 0, false), Err(OthelloError::OutOfBounds));
+        assert_eq!(AnyBoard::new(7), Err(OthelloError::UnsupportedSize(7)));
+        assert_eq!(
+            AnyBoard::new(6).unwrap().as_board(),
+            Err(OthelloError::NotEightByEight(6))
+        );
+    }
+}
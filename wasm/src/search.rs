@@ -0,0 +1,190 @@
+//! Bookkeeping shared by the bots that search the game tree: counting
+//! nodes, reporting progress and stopping when time runs out or when asked
+//! to from outside.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+/// How many nodes are visited between checks of the clock and the control
+const NODES_PER_CHECK: u64 = 4096;
+
+/// How far a search has got
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    /// The deepest search finished so far, in plies. 0 for searches that
+    /// don't go depth by depth, like MCTS.
+    pub depth: u16,
+    /// The best move found by the deepest finished search
+    pub best_move: Option<(i8, i8)>,
+    /// Positions looked at so far
+    pub nodes: u64,
+}
+
+/// Watches a running search and can stop it, ex. for a "move now" button
+pub trait SearchControl {
+    /// Polled every few thousand nodes and whenever the search finishes a
+    /// depth. Returns true to stop the search, in which case the bot plays
+    /// the best move found so far.
+    fn should_stop(&mut self, progress: &Progress) -> bool;
+}
+
+pub struct Search<'a> {
+    /// Positions looked at so far
+    pub nodes: u64,
+    depth: u16,
+    best_move: Option<(i8, i8)>,
+    deadline: Option<Instant>,
+    control: Option<&'a mut (dyn SearchControl + 'static)>,
+    stopped: bool,
+}
+
+impl<'a> Search<'a> {
+    /// Starts a search that stops after `time_limit`, if there is one, or
+    /// when `control` asks it to
+    pub fn new(
+        time_limit: Option<Duration>,
+        control: Option<&'a mut (dyn SearchControl + 'static)>,
+    ) -> Search<'a> {
+        Search {
+            nodes: 0,
+            depth: 0,
+            best_move: None,
+            deadline: time_limit.map(|limit| Instant::now() + limit),
+            control,
+            stopped: false,
+        }
+    }
+
+    /// Counts a visited node. Returns true if the search should stop, in
+    /// which case the result of the node is thrown away.
+    pub fn visit(&mut self) -> bool {
+        self.nodes += 1;
+
+        if !self.stopped && self.nodes.is_multiple_of(NODES_PER_CHECK) {
+            self.check();
+        }
+        self.stopped
+    }
+
+    /// Records the best move of a finished search to `depth` plies and lets
+    /// the control know
+    pub fn finish_depth(&mut self, depth: u16, best_move: (i8, i8)) {
+        self.depth = depth;
+        self.best_move = Some(best_move);
+        if !self.stopped {
+            self.check();
+        }
+    }
+
+    /// The best move of the deepest finished search
+    pub fn best_move(&self) -> Option<(i8, i8)> {
+        self.best_move
+    }
+
+    /// True once the search has run out of time or was stopped. Results
+    /// from after this point are incomplete.
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            depth: self.depth,
+            best_move: self.best_move,
+            nodes: self.nodes,
+        }
+    }
+
+    fn check(&mut self) {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.stopped = true;
+            return;
+        }
+
+        let progress = self.progress();
+        if let Some(control) = self.control.as_mut() {
+            self.stopped = control.should_stop(&progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stops after being polled a number of times, remembering the progress
+    struct StopAfter {
+        polls: usize,
+        seen: Vec<Progress>,
+    }
+
+    impl SearchControl for StopAfter {
+        fn should_stop(&mut self, progress: &Progress) -> bool {
+            self.seen.push(progress.clone());
+            self.seen.len() >= self.polls
+        }
+    }
+
+    #[test]
+    fn stops_at_deadline() {
+        let mut search = Search::new(Some(Duration::ZERO), None);
+        for _ in 1..NODES_PER_CHECK {
+            assert!(!search.visit());
+        }
+        assert!(search.visit());
+        assert!(search.stopped());
+
+        let mut search = Search::new(None, None);
+        for _ in 0..NODES_PER_CHECK * 2 {
+            assert!(!search.visit());
+        }
+        assert_eq!(search.nodes, NODES_PER_CHECK * 2);
+    }
+
+    #[test]
+    fn control_is_polled_and_stops() {
+        let mut control = StopAfter {
+            polls: 3,
+            seen: Vec::new(),
+        };
+        let mut search = Search::new(None, Some(&mut control));
+
+        for _ in 0..NODES_PER_CHECK {
+            search.visit();
+        }
+        search.finish_depth(1, (2, 3));
+        assert!(!search.stopped());
+        for _ in 0..NODES_PER_CHECK {
+            search.visit();
+        }
+        assert!(search.stopped());
+        assert_eq!(search.best_move(), Some((2, 3)));
+
+        assert_eq!(
+            control.seen,
+            vec![
+                Progress {
+                    depth: 0,
+                    best_move: None,
+                    nodes: NODES_PER_CHECK,
+                },
+                Progress {
+                    depth: 1,
+                    best_move: Some((2, 3)),
+                    nodes: NODES_PER_CHECK,
+                },
+                Progress {
+                    depth: 1,
+                    best_move: Some((2, 3)),
+                    nodes: NODES_PER_CHECK * 2,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! A game server for local network play, so that bots written in any
+//! language, or people, can play the built-in bots. See `src/bin/server.rs`
+//! and the reference client in `client`.
+//!
+//! Clients connect over TCP and send and receive one JSON object per line.
+//! A client starts a game with `newGame`, naming a built-in opponent, then
+//! answers each `yourTurn` with a `move` until it gets `gameOver`. Skips are
+//! played by the server, so a client is only asked when it has a valid
+//! move. A client that sends an invalid move, something other than a move,
+//! takes longer than the time limit or disconnects forfeits the game.
+//!
+//! The server only listens on localhost.
+
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::{
+    bots::{self, BotRunner},
+    error::OthelloError,
+    notation,
+    position::{AnyBoard, Position},
+    snapshot::GameResult,
+    variant::Variant,
+};
+
+pub mod client;
+
+/// The port the binaries use unless given another
+pub const DEFAULT_PORT: u16 = 7878;
+/// How long a player may think about each move unless given another limit
+pub const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(5);
+
+/// A message from a client to the server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ClientMessage {
+    /// Starts a game against a built-in bot
+    NewGame {
+        /// The client's name, for the results
+        name: String,
+        /// The bot to play, one of `bots::BOT_NAMES`
+        opponent: String,
+        /// The client's color: true = white; false = black. Black moves
+        /// first.
+        color: bool,
+        /// Squares along each side of the board, 8 if left out
+        #[serde(default)]
+        size: Option<u8>,
+        /// The rules of the game, standard Othello if left out
+        #[serde(default)]
+        variant: Variant,
+    },
+    /// The client's move as [x, y], answering `yourTurn`
+    Move {
+        #[serde(rename = "move")]
+        m: (i8, i8),
+    },
+}
+
+/// A message from the server to a client
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ServerMessage {
+    /// The game asked for with `newGame` has started
+    GameStarted {
+        color: bool,
+        opponent: String,
+        size: u8,
+        variant: Variant,
+        /// How long the client may take to answer each `yourTurn`
+        time_limit_ms: u32,
+    },
+    /// It's the client's turn
+    YourTurn {
+        /// The position, written as by `notation::format_board`
+        board: String,
+        legal_moves: Vec<(i8, i8)>,
+        /// The opponent's last move, None at the start of the game or if
+        /// the opponent had to skip
+        last_move: Option<(i8, i8)>,
+    },
+    GameOver(GameRecord),
+    /// The game couldn't be started, ex. because the opponent doesn't
+    /// exist. The server closes the connection after sending it.
+    Error {
+        message: String,
+    },
+}
+
+/// How a game ended, sent to the client and passed to the server's logger
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    /// The names of the players
+    pub black: String,
+    pub white: String,
+    pub result: GameResult,
+    pub black_count: u16,
+    pub white_count: u16,
+    /// The moves in notation separated by spaces, with "pass" for skips
+    pub moves: String,
+    /// Why a player forfeited, None if the game was played to the end
+    pub forfeit: Option<String>,
+}
+
+pub struct Server {
+    listener: TcpListener,
+    time_limit: Duration,
+}
+
+impl Server {
+    /// Listens on `addr`, ex. "127.0.0.1:7878", giving each player
+    /// `time_limit` per move.
+    ///
+    /// Returns an error if `addr` isn't a localhost address or can't be
+    /// listened on.
+    pub fn bind(addr: impl ToSocketAddrs, time_limit: Duration) -> io::Result<Server> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+        if addrs.iter().any(|addr| !addr.ip().is_loopback()) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "The server only listens on localhost",
+            ));
+        }
+
+        Ok(Server {
+            listener: TcpListener::bind(&addrs[..])?,
+            time_limit,
+        })
+    }
+
+    /// The address the server listens on, with the port chosen by the
+    /// system if it was bound to port 0
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Hosts a game for each client that connects, each on its own thread,
+    /// calling `log` with the record of every finished game. Never returns
+    /// unless the server stops listening.
+    pub fn serve(&self, log: impl Fn(&GameRecord) + Send + Sync + 'static) -> io::Result<()> {
+        let log = Arc::new(log);
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let log = Arc::clone(&log);
+            let time_limit = self.time_limit;
+
+            thread::spawn(move || {
+                // a client that disconnects before the game starts has no
+                // game to log
+                if let Ok(Some(record)) = host_game(stream, time_limit) {
+                    log(&record);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Plays one game against the client on `stream`. Returns its record, or
+/// None if the game couldn't be started.
+fn host_game(stream: TcpStream, time_limit: Duration) -> io::Result<Option<GameRecord>> {
+    let mut connection = Connection::new(stream)?;
+
+    let Some(line) = connection.read_line()? else {
+        return Ok(None);
+    };
+    let Ok(ClientMessage::NewGame {
+        name,
+        opponent,
+        color,
+        size,
+        variant,
+    }) = serde_json::from_str(&line)
+    else {
+        connection.send_error("Expected a newGame message")?;
+        return Ok(None);
+    };
+
+    let Some(mut bot) = bots::create_bot(&opponent) else {
+        connection.send_error(&OthelloError::UnknownBot(opponent).to_string())?;
+        return Ok(None);
+    };
+    let size = size.unwrap_or(8);
+    let start = match AnyBoard::new(size) {
+        Ok(start) => start,
+        Err(err) => {
+            connection.send_error(&err.to_string())?;
+            return Ok(None);
+        }
+    };
+
+    bot.set_time_limit(Some(time_limit));
+    let (black_bot, white_bot) = if color {
+        (Some(bot), None)
+    } else {
+        (None, Some(bot))
+    };
+    let mut runner = BotRunner::from_start(black_bot, white_bot, start, false, variant);
+
+    connection.send(&ServerMessage::GameStarted {
+        color,
+        opponent: opponent.clone(),
+        size,
+        variant,
+        time_limit_ms: time_limit.as_millis() as u32,
+    })?;
+
+    let mut forfeit = None;
+    while let Some(turn) = runner.game.turn() {
+        let played = if turn == color {
+            connection.client_move(&mut runner, color, time_limit)
+        } else if turn {
+            runner
+                .run_white_bot()
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        } else {
+            runner
+                .run_black_bot()
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        };
+
+        if let Err(reason) = played {
+            forfeit = Some((turn, reason));
+            break;
+        }
+    }
+
+    let board = runner.game.board();
+    let (black, white) = if color {
+        (opponent, name)
+    } else {
+        (name, opponent)
+    };
+    let winner = match &forfeit {
+        Some((loser, _)) => Some(!loser),
+        None => variant.winner(board),
+    };
+    let record = GameRecord {
+        black,
+        white,
+        result: match winner {
+            Some(false) => GameResult::BlackWins,
+            Some(true) => GameResult::WhiteWins,
+            None => GameResult::Draw,
+        },
+        black_count: board.count_pieces(false),
+        white_count: board.count_pieces(true),
+        moves: runner.game.history_notation().join(" "),
+        forfeit: forfeit.map(|(_, reason)| reason),
+    };
+
+    // the client may have gone, but the game still counts
+    let _ = connection.send(&ServerMessage::GameOver(record.clone()));
+    Ok(Some(record))
+}
+
+/// A client's connection, read and written a line at a time
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Connection> {
+        // messages are small and answered at once, so don't wait to batch them
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Reads a line without its line break. None if the client has
+    /// disconnected.
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end().to_string()))
+    }
+
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        writeln!(self.writer, "{json}")?;
+        self.writer.flush()
+    }
+
+    fn send_error(&mut self, message: &str) -> io::Result<()> {
+        self.send(&ServerMessage::Error {
+            message: message.to_string(),
+        })
+    }
+
+    /// Asks the client for its move and plays it. Returns why the client
+    /// forfeits if it doesn't send a valid move in time.
+    fn client_move(
+        &mut self,
+        runner: &mut BotRunner<AnyBoard>,
+        color: bool,
+        time_limit: Duration,
+    ) -> Result<(), String> {
+        let game = &runner.game;
+        self.send(&ServerMessage::YourTurn {
+            board: notation::format_board(game.board()),
+            legal_moves: game.board().get_all_valid_moves(color),
+            last_move: game.history().last().and_then(|ply| ply.m),
+        })
+        .map_err(|err| err.to_string())?;
+
+        let start = Instant::now();
+        self.writer
+            .set_read_timeout(Some(time_limit.max(Duration::from_millis(1))))
+            .map_err(|err| err.to_string())?;
+        let line = match self.read_line() {
+            Ok(Some(line)) => line,
+            Ok(None) => return Err("Disconnected".to_string()),
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Err("Ran out of time".to_string());
+            }
+            Err(err) => return Err(err.to_string()),
+        };
+        if start.elapsed() > time_limit {
+            return Err("Ran out of time".to_string());
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(ClientMessage::Move { m: (x, y) }) => runner
+                .game
+                .play(x, y, color)
+                .map_err(|err| format!("Invalid move {}: {err}", notation::format_move((x, y)))),
+            _ => Err(format!("Expected a move, not {line}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_messages() {
+        let json = r#"{"type": "newGame", "name": "me", "opponent": "random_bot", "color": true}"#;
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(json).unwrap(),
+            ClientMessage::NewGame {
+                name: "me".to_string(),
+                opponent: "random_bot".to_string(),
+                color: true,
+                size: None,
+                variant: Variant::Standard,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientMessage::Move { m: (5, 4) }).unwrap(),
+            r#"{"type":"move","move":[5,4]}"#
+        );
+
+        let message = ServerMessage::YourTurn {
+            board: notation::format_board(&AnyBoard::new(4).unwrap()),
+            legal_moves: vec![(1, 0)],
+            last_move: None,
+        };
+        let json = serde_json::to_string(&message).unwrap();
+        assert!(json.starts_with(r#"{"type":"yourTurn","#) && json.contains("\"legalMoves\""));
+        assert_eq!(
+            serde_json::from_str::<ServerMessage>(&json).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn only_localhost() {
+        let err = Server::bind("0.0.0.0:0", DEFAULT_TIME_LIMIT).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(Server::bind("127.0.0.1:0", DEFAULT_TIME_LIMIT).is_ok());
+    }
+}
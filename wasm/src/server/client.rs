@@ -0,0 +1,84 @@
+//! A reference client for the game server, which plays a game with a bot or
+//! any other way of choosing moves. See `src/bin/client.rs`.
+
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use crate::{
+    bots::MakeMove,
+    notation,
+    position::{AnyBoard, Position},
+    server::{ClientMessage, GameRecord, ServerMessage},
+};
+
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Client> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        writeln!(self.writer, "{json}")?;
+        self.writer.flush()
+    }
+
+    /// Waits for the server's next message. Returns an error if the server
+    /// has closed the connection or sent something that isn't a message.
+    pub fn receive(&mut self) -> io::Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Starts a game with `new_game` and plays it to the end, calling
+    /// `choose` with the position and the client's color for each move.
+    ///
+    /// Returns an error if the server sends an `error` message or the
+    /// connection fails.
+    pub fn play_game(
+        &mut self,
+        new_game: &ClientMessage,
+        mut choose: impl FnMut(&AnyBoard, bool) -> (i8, i8),
+    ) -> io::Result<GameRecord> {
+        self.send(new_game)?;
+        let mut color = false;
+
+        loop {
+            match self.receive()? {
+                ServerMessage::GameStarted { color: c, .. } => color = c,
+                ServerMessage::YourTurn { board, .. } => {
+                    let board = notation::parse_any_board(&board).ok_or_else(|| {
+                        io::Error::new(ErrorKind::InvalidData, "The board couldn't be read")
+                    })?;
+                    let m = choose(&board, color);
+                    self.send(&ClientMessage::Move { m })?;
+                }
+                ServerMessage::GameOver(record) => return Ok(record),
+                ServerMessage::Error { message } => return Err(io::Error::other(message)),
+            }
+        }
+    }
+
+    /// `play_game` with `bot` choosing the moves
+    pub fn play_bot(
+        &mut self,
+        new_game: &ClientMessage,
+        bot: &mut dyn MakeMove,
+    ) -> io::Result<GameRecord> {
+        self.play_game(new_game, |board, color| board.ask_bot(bot, color))
+    }
+}
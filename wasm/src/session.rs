@@ -0,0 +1,355 @@
+//! Games that are running at the same time, each with its own board and bots.
+
+use std::{collections::HashMap, time::Duration};
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    bots::{self, BotRunner, MakeMove, level_bot},
+    clock::TimeControl,
+    error::OthelloError,
+    notation,
+    position::{AnyBoard, Position},
+    setup,
+    variant::Variant,
+};
+
+/// Settings for a new game. Players without a bot are played by a human.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct GameConfig {
+    black_bot: Option<String>,
+    white_bot: Option<String>,
+    variant: Variant,
+    /// Squares along each side, 8 if not set
+    size: Option<u8>,
+    /// The starting position in notation, instead of the usual cross
+    start: Option<String>,
+    /// Squares that are blocked for the whole game, in notation
+    blocked: Option<String>,
+    /// The color receiving the handicap and the number of corners
+    handicap: Option<(bool, u8)>,
+    first_turn: Option<bool>,
+    /// None for games without clocks
+    time_control: Option<TimeControl>,
+}
+
+#[wasm_bindgen]
+impl GameConfig {
+    pub fn new() -> GameConfig {
+        GameConfig::default()
+    }
+
+    /// Sets the bot playing black by name, ex. "deep_heuristic_bot"
+    pub fn set_black_bot(&mut self, name: &str) {
+        self.black_bot = Some(name.to_string());
+    }
+
+    /// Sets the bot playing white by name, ex. "deep_heuristic_bot"
+    pub fn set_white_bot(&mut self, name: &str) {
+        self.white_bot = Some(name.to_string());
+    }
+
+    /// Sets the bot playing black to a difficulty level from 1 to 10. Other
+    /// levels make `JsInterface.create_game` throw an `InvalidLevel` error.
+    pub fn set_black_level(&mut self, level: u8) {
+        self.black_bot = Some(level_bot::name(level));
+    }
+
+    /// Sets the bot playing white to a difficulty level from 1 to 10. Other
+    /// levels make `JsInterface.create_game` throw an `InvalidLevel` error.
+    pub fn set_white_level(&mut self, level: u8) {
+        self.white_bot = Some(level_bot::name(level));
+    }
+
+    /// Sets the rules of the game, standard Othello by default
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// Sets the number of squares along each side of the board, an even
+    /// number from 4 to 10. Boards are 8x8 by default.
+    pub fn set_size(&mut self, size: u8) {
+        self.size = Some(size);
+    }
+
+    /// Starts from a position written as by `notation::format_board`,
+    /// instead of the usual cross. The board size is found from the
+    /// position.
+    pub fn set_start_position(&mut self, position: &str) {
+        self.start = Some(position.to_string());
+    }
+
+    /// Blocks squares for the whole game, written as a list of moves, ex.
+    /// "a1h8"
+    pub fn set_blocked_squares(&mut self, squares: &str) {
+        self.blocked = Some(squares.to_string());
+    }
+
+    /// Gives `color` discs on 0 to 4 corners. The other player moves first,
+    /// unless set with `set_first_turn`.
+    pub fn set_handicap(&mut self, color: bool, corners: u8) {
+        self.handicap = Some((color, corners));
+    }
+
+    /// Sets the color that moves first, black by default
+    pub fn set_first_turn(&mut self, color: bool) {
+        self.first_turn = Some(color);
+    }
+
+    /// Gives each player `total_ms` for the whole game, with `increment_ms`
+    /// added after each of their moves. Games have no clocks by default.
+    pub fn set_time_control(&mut self, total_ms: u32, increment_ms: u32) {
+        self.time_control = Some(TimeControl::Total {
+            total: Duration::from_millis(total_ms.into()),
+            increment: Duration::from_millis(increment_ms.into()),
+        });
+    }
+
+    /// Gives each player `ms` for every move
+    pub fn set_time_per_move(&mut self, ms: u32) {
+        self.time_control = Some(TimeControl::PerMove(Duration::from_millis(ms.into())));
+    }
+}
+
+impl GameConfig {
+    /// The position the game starts from and the color that moves first.
+    ///
+    /// Returns an error if the position, the size, the blocked squares or
+    /// the handicap can't be used.
+    pub fn start(&self) -> Result<(AnyBoard, bool), OthelloError> {
+        let mut start = match &self.start {
+            Some(position) => {
+                notation::parse_any_board(position).ok_or(OthelloError::InvalidBoard)?
+            }
+            None => AnyBoard::new(self.size.unwrap_or(8))?,
+        };
+
+        if let Some(squares) = &self.blocked {
+            let squares = notation::parse_moves_sized(squares, start.size())
+                .ok_or(OthelloError::InvalidRecord)?;
+            setup::block_squares(&mut start, &squares)?;
+        }
+
+        let mut first = false;
+        if let Some((color, corners)) = self.handicap {
+            setup::add_handicap(&mut start, color, corners)?;
+            if corners > 0 {
+                first = setup::handicap_first_turn(color);
+            }
+        }
+
+        Ok((start, self.first_turn.unwrap_or(first)))
+    }
+}
+
+/// Games by id. Ids are not reused after a game is removed.
+pub struct Sessions {
+    games: HashMap<u32, BotRunner<AnyBoard>>,
+    next_id: u32,
+}
+
+impl Sessions {
+    pub fn new() -> Sessions {
+        Sessions {
+            games: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts a new game and returns its id.
+    /// Returns an error if a bot name isn't known or the game can't start
+    /// as set up, see `GameConfig::start`.
+    pub fn create(&mut self, config: &GameConfig) -> Result<u32, OthelloError> {
+        let black_bot = Self::create_bot(&config.black_bot)?;
+        let white_bot = Self::create_bot(&config.white_bot)?;
+        let (start, first) = config.start()?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut runner = BotRunner::from_start(black_bot, white_bot, start, first, config.variant);
+        runner.set_time_control(config.time_control);
+        self.games.insert(id, runner);
+        Ok(id)
+    }
+
+    pub fn get(&self, id: u32) -> Result<&BotRunner<AnyBoard>, OthelloError> {
+        self.games.get(&id).ok_or(OthelloError::NoGame(id))
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Result<&mut BotRunner<AnyBoard>, OthelloError> {
+        self.games.get_mut(&id).ok_or(OthelloError::NoGame(id))
+    }
+
+    /// Ends a game, freeing it. Returns false if there was no game with the id.
+    pub fn remove(&mut self, id: u32) -> bool {
+        self.games.remove(&id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    fn create_bot(name: &Option<String>) -> Result<Option<Box<dyn MakeMove>>, OthelloError> {
+        match name {
+            Some(name) => match bots::create_bot(name) {
+                Some(bot) => Ok(Some(bot)),
+                None => match level_bot::parse_name(name) {
+                    Some(level) => Err(OthelloError::InvalidLevel(level)),
+                    None => Err(OthelloError::UnknownBot(name.clone())),
+                },
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn games_are_independent() {
+        let mut sessions = Sessions::new();
+        let human_game = sessions.create(&GameConfig::new()).unwrap();
+
+        let mut config = GameConfig::new();
+        config.set_black_bot("first_valid_bot");
+        config.set_white_bot("last_valid_bot");
+        let bot_game = sessions.create(&config).unwrap();
+        assert_ne!(human_game, bot_game);
+
+        sessions
+            .get_mut(bot_game)
+            .unwrap()
+            .run_game_to_end()
+            .unwrap();
+        assert_eq!(sessions.get(bot_game).unwrap().game.turn(), None);
+        assert!(sessions.get(human_game).unwrap().game.history().is_empty());
+
+        sessions
+            .get_mut(human_game)
+            .unwrap()
+            .game
+            .play(5, 4, false)
+            .unwrap();
+        assert_eq!(sessions.get(human_game).unwrap().game.history().len(), 1);
+    }
+
+    #[test]
+    fn removed_ids_are_not_reused() {
+        let mut sessions = Sessions::new();
+        let first = sessions.create(&GameConfig::new()).unwrap();
+        assert!(sessions.remove(first));
+        assert!(!sessions.remove(first));
+        assert!(sessions.is_empty());
+
+        let second = sessions.create(&GameConfig::new()).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(sessions.get(first).err(), Some(OthelloError::NoGame(first)));
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[test]
+    fn unknown_bot() {
+        let mut sessions = Sessions::new();
+        let mut config = GameConfig::new();
+        config.set_white_bot("no_such_bot");
+        assert_eq!(
+            sessions.create(&config).err(),
+            Some(OthelloError::UnknownBot("no_such_bot".to_string()))
+        );
+        assert!(sessions.is_empty());
+
+        config.set_white_level(11);
+        assert_eq!(
+            sessions.create(&config).err(),
+            Some(OthelloError::InvalidLevel(11))
+        );
+        config.set_white_level(3);
+        assert!(sessions.create(&config).is_ok());
+    }
+
+    #[test]
+    fn board_sizes() {
+        let mut sessions = Sessions::new();
+        let mut config = GameConfig::new();
+        config.set_black_bot("random_bot");
+        config.set_white_bot("deep_heuristic_bot");
+        config.set_size(10);
+        let id = sessions.create(&config).unwrap();
+
+        let runner = sessions.get_mut(id).unwrap();
+        assert_eq!(runner.game.board().size(), 10);
+        runner.run_game_to_end().unwrap();
+        assert_eq!(runner.game.turn(), None);
+        // games can end with empty squares when neither player can move
+        let pieces =
+            runner.game.board().count_pieces(false) + runner.game.board().count_pieces(true);
+        assert!((5..=100).contains(&pieces));
+
+        config.set_size(7);
+        assert_eq!(
+            sessions.create(&config).err(),
+            Some(OthelloError::UnsupportedSize(7))
+        );
+    }
+
+    #[test]
+    fn time_controls() {
+        let mut sessions = Sessions::new();
+        let id = sessions.create(&GameConfig::new()).unwrap();
+        assert!(sessions.get(id).unwrap().clock().is_none());
+
+        let mut config = GameConfig::new();
+        config.set_time_control(60_000, 1_000);
+        let id = sessions.create(&config).unwrap();
+        let clock = sessions.get(id).unwrap().clock().unwrap();
+        assert_eq!(clock.running(), Some(false));
+        assert_eq!(clock.remaining(true), Duration::from_secs(60));
+
+        config.set_time_per_move(500);
+        let id = sessions.create(&config).unwrap();
+        let clock = sessions.get(id).unwrap().clock().unwrap();
+        assert_eq!(
+            clock.control(),
+            TimeControl::PerMove(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn custom_starts() {
+        let mut sessions = Sessions::new();
+        let mut config = GameConfig::new();
+        config.set_handicap(false, 2);
+        config.set_blocked_squares("d3 c4");
+        let id = sessions.create(&config).unwrap();
+
+        let game = &sessions.get(id).unwrap().game;
+        assert_eq!(game.turn(), Some(true));
+        assert_eq!(game.board().count_pieces(false), 4);
+        assert!(game.board().is_blocked(3, 2) && game.board().is_blocked(2, 3));
+
+        let mut config = GameConfig::new();
+        config.set_start_position(&format!("X{}", "-".repeat(35)));
+        config.set_first_turn(true);
+        config.set_black_bot("first_valid_bot");
+        config.set_white_bot("first_valid_bot");
+        let id = sessions.create(&config).unwrap();
+        // no one can move from a lone disc
+        assert_eq!(sessions.get(id).unwrap().game.turn(), None);
+        assert_eq!(sessions.get(id).unwrap().game.board().size(), 6);
+
+        config.set_blocked_squares("a1");
+        assert_eq!(sessions.create(&config).err(), Some(OthelloError::Occupied));
+        config.set_start_position("XO");
+        assert_eq!(
+            sessions.create(&config).err(),
+            Some(OthelloError::InvalidBoard)
+        );
+    }
+}
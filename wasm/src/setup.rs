@@ -0,0 +1,108 @@
+//! Starting positions other than the usual cross: handicaps, which give the
+//! weaker player discs on the corners, and "holes" variants, where some
+//! squares are blocked for the whole game.
+
+use crate::{error::OthelloError, position::Position};
+
+/// Most corners a handicap can give
+pub const MAX_HANDICAP: u8 = 4;
+
+/// Gives `color` a disc on each of the first `corners` corners, in the order
+/// top left, bottom right, top right, bottom left (a1, h8, h1, a8 on 8x8).
+///
+/// In handicap games the player receiving the corners moves second, see
+/// `handicap_first_turn`.
+///
+/// Returns an error if `corners` is more than `MAX_HANDICAP` or a corner
+/// isn't empty.
+pub fn add_handicap<B: Position>(
+    board: &mut B,
+    color: bool,
+    corners: u8,
+) -> Result<(), OthelloError> {
+    if corners > MAX_HANDICAP {
+        return Err(OthelloError::InvalidHandicap(corners));
+    }
+
+    let last = board.size() - 1;
+    let order = [(0, 0), (last, last), (last, 0), (0, last)];
+    for (x, y) in order.into_iter().take(corners as usize) {
+        if board.is_occupied(x, y) {
+            return Err(OthelloError::Occupied);
+        }
+        if board.is_blocked(x, y) {
+            return Err(OthelloError::Blocked);
+        }
+        board.set(x, y, color);
+    }
+    Ok(())
+}
+
+/// The color that moves first when `color` receives a handicap
+pub fn handicap_first_turn(color: bool) -> bool {
+    !color
+}
+
+/// Blocks squares so that no piece can be placed on them.
+///
+/// Returns an error if a square is off the board or has a piece on it.
+pub fn block_squares<B: Position>(board: &mut B, squares: &[(i8, i8)]) -> Result<(), OthelloError> {
+    for &(x, y) in squares {
+        if !board.is_on_board(x, y) {
+            return Err(OthelloError::OutOfBounds);
+        }
+        if board.is_occupied(x, y) {
+            return Err(OthelloError::Occupied);
+        }
+        board.set_blocked(x, y);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, sized_board::SizedBoard};
+
+    #[test]
+    fn handicaps() {
+        let mut board = Board::new();
+        add_handicap(&mut board, false, 2).unwrap();
+        assert!(board.is_occupied(0, 0) && !board.get_color(0, 0));
+        assert!(board.is_occupied(7, 7) && !board.get_color(7, 7));
+        assert!(!board.is_occupied(7, 0));
+        assert_eq!(board.count_pieces(false), 4);
+        assert!(handicap_first_turn(false));
+
+        let mut board = SizedBoard::new(6).unwrap();
+        add_handicap(&mut board, true, 4).unwrap();
+        assert_eq!(board.count_pieces(true), 6);
+        assert!(board.is_occupied(5, 0) && board.is_occupied(0, 5));
+
+        assert_eq!(
+            add_handicap(&mut Board::new(), false, 5),
+            Err(OthelloError::InvalidHandicap(5))
+        );
+        assert_eq!(
+            add_handicap(&mut board, false, 1),
+            Err(OthelloError::Occupied)
+        );
+    }
+
+    #[test]
+    fn blocked_squares() {
+        let mut board = Board::new();
+        block_squares(&mut board, &[(5, 4), (2, 3)]).unwrap();
+        assert_eq!(board.get_all_valid_moves(false), vec![(3, 2), (4, 5)]);
+        assert_eq!(board.place_chip(5, 4, false), Err(OthelloError::Blocked));
+
+        assert_eq!(
+            block_squares(&mut board, &[(3, 3)]),
+            Err(OthelloError::Occupied)
+        );
+        assert_eq!(
+            block_squares(&mut board, &[(8, 0)]),
+            Err(OthelloError::OutOfBounds)
+        );
+    }
+}
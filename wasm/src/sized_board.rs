@@ -0,0 +1,243 @@
+//! Boards of other sizes than 8x8, ex. 6x6, which is solved, or 10x10.
+
+use std::fmt::Debug;
+
+use crate::{
+    bots::MakeMove,
+    error::OthelloError,
+    position::{self, Position},
+};
+
+/// Smallest board size supported
+pub const MIN_SIZE: u8 = 4;
+/// Largest board size supported, so that a board fits in a `u128`
+pub const MAX_SIZE: u8 = 10;
+
+/// A board of any even size from `MIN_SIZE` to `MAX_SIZE`. Bit y * size + x
+/// is the square (x, y).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SizedBoard {
+    size: i8,
+    pub filled: u128,
+    pub color: u128,
+    /// Squares where no piece can be placed
+    pub blocked: u128,
+}
+
+impl SizedBoard {
+    /// The starting position: two pieces of each color crossed in the
+    /// middle, white on the top left, like the 8x8 board.
+    ///
+    /// Returns an error if the size is odd or not from `MIN_SIZE` to
+    /// `MAX_SIZE`.
+    pub fn new(size: u8) -> Result<SizedBoard, OthelloError> {
+        let mut board = SizedBoard::empty(size)?;
+        let mid = board.size / 2;
+        board.set(mid - 1, mid - 1, true);
+        board.set(mid, mid, true);
+        board.set(mid, mid - 1, false);
+        board.set(mid - 1, mid, false);
+        Ok(board)
+    }
+
+    /// A board with no pieces on it
+    pub fn empty(size: u8) -> Result<SizedBoard, OthelloError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) || !size.is_multiple_of(2) {
+            return Err(OthelloError::UnsupportedSize(size));
+        }
+
+        Ok(SizedBoard {
+            size: size as i8,
+            filled: 0,
+            color: 0,
+            blocked: 0,
+        })
+    }
+
+    /// The bit of square (x, y). Squares off the board would land on
+    /// another row, or past the end of the board.
+    fn bit(&self, x: i8, y: i8) -> u128 {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        1u128 << (y as u32 * self.size as u32 + x as u32)
+    }
+}
+
+impl Position for SizedBoard {
+    fn size(&self) -> i8 {
+        self.size
+    }
+
+    fn is_occupied(&self, x: i8, y: i8) -> bool {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        self.filled & self.bit(x, y) != 0
+    }
+
+    fn get_color(&self, x: i8, y: i8) -> bool {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        self.color & self.bit(x, y) != 0
+    }
+
+    fn set(&mut self, x: i8, y: i8, color: bool) {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        let bit = self.bit(x, y);
+        self.filled |= bit;
+
+        if color {
+            self.color |= bit;
+        } else {
+            self.color &= !bit;
+        }
+    }
+
+    fn is_blocked(&self, x: i8, y: i8) -> bool {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        self.blocked & self.bit(x, y) != 0
+    }
+
+    fn set_blocked(&mut self, x: i8, y: i8) {
+        debug_assert!(self.is_on_board(x, y), "({x}, {y}) is off the board");
+        self.blocked |= self.bit(x, y);
+    }
+
+    fn try_place_chip(&mut self, x: i8, y: i8, color: bool) -> bool {
+        let mut flips = Vec::new();
+        if !position::capture_lines(self, x, y, color, |x, y| flips.push((x, y))) {
+            return false;
+        }
+
+        for (x, y) in flips {
+            self.set(x, y, color);
+        }
+        self.set(x, y, color);
+        true
+    }
+
+    fn is_valid_move(&self, x: i8, y: i8, color: bool) -> bool {
+        position::capture_lines(self, x, y, color, |_, _| {})
+    }
+
+    fn count_pieces(&self, color: bool) -> u16 {
+        let pieces = self.filled & if color { self.color } else { !self.color };
+        pieces.count_ones() as u16
+    }
+
+    fn ask_bot(&self, bot: &mut dyn MakeMove, color: bool) -> (i8, i8) {
+        bot.make_move_sized(self, color)
+    }
+}
+
+/// Draws the board like `Board` does
+impl Debug for SizedBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut res = format!("Board {}x{}:\n", self.size, self.size);
+        for y in 0..self.size {
+            res.push('[');
+            res.push(' ');
+
+            for x in 0..self.size {
+                res.push(if self.is_occupied(x, y) {
+                    if self.get_color(x, y) { 'O' } else { 'X' }
+                } else if self.is_blocked(x, y) {
+                    '#'
+                } else {
+                    '_'
+                });
+                res.push(' ');
+            }
+
+            res.push(']');
+            res.push('\n');
+        }
+
+        f.write_str(&res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    /// Counts positions like `Board::perft`
+    fn perft(board: &SizedBoard, color: bool, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = board.get_all_valid_moves(color);
+        if moves.is_empty() {
+            if !board.has_valid_move(!color) {
+                return 1;
+            }
+            return perft(board, !color, depth - 1);
+        }
+
+        moves
+            .into_iter()
+            .map(|(x, y)| {
+                let mut future = board.clone();
+                future.try_place_chip(x, y, color);
+                perft(&future, !color, depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn sizes() {
+        for size in [4, 6, 8, 10] {
+            let board = SizedBoard::new(size).unwrap();
+            assert_eq!(board.size(), size as i8);
+            assert_eq!(board.count_pieces(false), 2);
+            assert_eq!(board.count_pieces(true), 2);
+            assert_eq!(board.get_all_valid_moves(false).len(), 4);
+        }
+
+        for size in [0, 2, 7, 12] {
+            assert_eq!(
+                SizedBoard::new(size),
+                Err(OthelloError::UnsupportedSize(size))
+            );
+        }
+    }
+
+    #[test]
+    fn same_rules_as_board() {
+        let sized = SizedBoard::new(8).unwrap();
+        for depth in 0..5 {
+            assert_eq!(
+                perft(&sized, false, depth),
+                Board::new().perft(false, depth)
+            );
+        }
+    }
+
+    #[test]
+    fn edges_of_larger_boards() {
+        // black at (9, 9) captures along the bottom row of a 10x10 board
+        let mut board = SizedBoard::empty(10).unwrap();
+        board.set(7, 9, false);
+        board.set(8, 9, true);
+        assert!(board.is_valid_move(9, 9, false));
+        assert!(!board.is_valid_move(9, 8, false));
+        assert_eq!(board.flipped_squares(9, 9, false), vec![(8, 9)]);
+        assert_eq!(
+            board.place_chip(10, 9, false),
+            Err(OthelloError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "(6, 0) is off the board")]
+    fn unchecked_accessors_assert() {
+        // (6, 0) would read (0, 1) on a 6x6 board
+        SizedBoard::new(6).unwrap().is_occupied(6, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "(0, -1) is off the board")]
+    fn unchecked_setters_assert() {
+        SizedBoard::new(6).unwrap().set_blocked(0, -1);
+    }
+}
@@ -0,0 +1,167 @@
+//! Everything the page needs to draw a game, in one object, so the front end
+//! doesn't have to decode bitboards or know the rules.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{game::Game, position::Position};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GameResult {
+    BlackWins,
+    WhiteWins,
+    Draw,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Squares along each side of the board
+    pub size: i8,
+    /// size * size cells, index y * size + x: 0 = empty; 1 = white;
+    /// -1 = black; 2 = blocked
+    pub cells: Vec<i8>,
+    /// The color to play next, None if the game is over
+    pub turn: Option<bool>,
+    pub black_count: u16,
+    pub white_count: u16,
+    /// Valid moves for the color to play next
+    pub legal_moves: Vec<(i8, i8)>,
+    pub last_move: Option<(i8, i8)>,
+    /// Pieces flipped by the last move
+    pub last_flipped: Vec<(i8, i8)>,
+    /// True if black had to skip since the last move
+    pub black_passed: bool,
+    pub white_passed: bool,
+    pub game_over: bool,
+    /// None until the game is over. Follows the rules of the game's variant.
+    pub result: Option<GameResult>,
+}
+
+/// Takes a snapshot of the current position of a game
+pub fn snapshot<B: Position>(game: &Game<B>) -> Snapshot {
+    let board = game.board();
+    let turn = game.turn();
+    let size = board.size();
+
+    let cells = (0..size * size)
+        .map(|i| {
+            let (x, y) = (i % size, i / size);
+            if board.is_blocked(x, y) {
+                return 2;
+            }
+            match (board.is_occupied(x, y), board.get_color(x, y)) {
+                (false, _) => 0,
+                (true, true) => 1,
+                (true, false) => -1,
+            }
+        })
+        .collect();
+
+    let black_count = board.count_pieces(false);
+    let white_count = board.count_pieces(true);
+
+    let passed = |color: bool| {
+        game.history()
+            .iter()
+            .rev()
+            .take_while(|ply| ply.m.is_none())
+            .any(|ply| ply.color == color)
+    };
+
+    Snapshot {
+        size,
+        cells,
+        turn,
+        black_count,
+        white_count,
+        legal_moves: turn.map_or(Vec::new(), |color| board.get_all_valid_moves(color)),
+        last_move: game.last_move(),
+        last_flipped: game.last_move_flips(),
+        black_passed: passed(false),
+        white_passed: passed(true),
+        game_over: turn.is_none(),
+        result: turn.is_none().then(|| match game.variant().winner(board) {
+            Some(false) => GameResult::BlackWins,
+            Some(true) => GameResult::WhiteWins,
+            None => GameResult::Draw,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, notation, sized_board::SizedBoard, variant::Variant};
+
+    fn play_record(record: &str) -> Game {
+        let mut game = Game::new();
+        for (x, y) in notation::parse_moves(record).unwrap() {
+            let color = game.turn().unwrap();
+            game.play(x, y, color).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn starting_position() {
+        let snapshot = snapshot(&Game::new());
+        assert_eq!(snapshot.size, 8);
+        assert_eq!(snapshot.cells.len(), 64);
+        assert_eq!(snapshot.cells[3 * 8 + 3], 1);
+        assert_eq!(snapshot.cells[3 * 8 + 4], -1);
+        assert_eq!(snapshot.cells.iter().filter(|cell| **cell != 0).count(), 4);
+        assert_eq!(snapshot.turn, Some(false));
+        assert_eq!((snapshot.black_count, snapshot.white_count), (2, 2));
+        assert_eq!(snapshot.legal_moves.len(), 4);
+        assert_eq!(snapshot.last_move, None);
+        assert!(snapshot.last_flipped.is_empty());
+        assert!(!snapshot.black_passed && !snapshot.white_passed);
+        assert!(!snapshot.game_over);
+        assert_eq!(snapshot.result, None);
+
+        let ten = super::snapshot(&Game::from_start(
+            SizedBoard::new(10).unwrap(),
+            false,
+            Variant::Standard,
+        ));
+        assert_eq!(ten.size, 10);
+        assert_eq!(ten.cells.len(), 100);
+        assert_eq!(ten.cells[4 * 10 + 4], 1);
+        assert_eq!(ten.cells[4 * 10 + 5], -1);
+        assert_eq!(ten.legal_moves.len(), 4);
+
+        let mut holes = Board::new();
+        holes.set_blocked(0, 0);
+        let holes = super::snapshot(&Game::from_start(holes, false, Variant::Standard));
+        assert_eq!(holes.cells[0], 2);
+    }
+
+    #[test]
+    fn moves_skips_and_game_end() {
+        let after_f5 = snapshot(&play_record("f5"));
+        assert_eq!(after_f5.last_move, Some((5, 4)));
+        assert_eq!(after_f5.last_flipped, vec![(4, 4)]);
+        assert_eq!(after_f5.turn, Some(true));
+
+        // black skips after white's a3
+        let skipped = snapshot(&play_record("c4c3e6b4a4a5c2a3"));
+        assert!(skipped.black_passed && !skipped.white_passed);
+        assert_eq!(skipped.turn, Some(true));
+        let mut game = play_record("c4c3e6b4a4a5c2a3");
+        game.play(5, 3, true).unwrap();
+        assert!(!snapshot(&game).black_passed);
+
+        let over = snapshot(&play_record("f5f6c4c5f7b3b5e6a2"));
+        assert!(over.game_over);
+        assert!(over.legal_moves.is_empty());
+        assert_eq!(over.result, Some(GameResult::BlackWins));
+
+        let mut anti = Game::with_variant(Variant::AntiOthello);
+        for (x, y) in notation::parse_moves("f5f6c4c5f7b3b5e6a2").unwrap() {
+            let color = anti.turn().unwrap();
+            anti.play(x, y, color).unwrap();
+        }
+        assert_eq!(snapshot(&anti).result, Some(GameResult::WhiteWins));
+    }
+}
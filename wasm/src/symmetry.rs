@@ -0,0 +1,176 @@
+//! The eight rotations and reflections of the board.
+//!
+//! Bitboards use bit `y * 8 + x` for the square (x, y), with y increasing
+//! downwards. Rotations are clockwise as the board is drawn.
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors left and right
+    FlipHorizontal,
+    /// Mirrors top and bottom
+    FlipVertical,
+    /// Mirrors along the diagonal from (0, 0) to (7, 7)
+    FlipDiagonal,
+    /// Mirrors along the diagonal from (7, 0) to (0, 7)
+    FlipAntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// The symmetry that undoes this one
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            s => s,
+        }
+    }
+
+    /// Moves the square (x, y) to where it is after the transform.
+    /// Moves outside the board, like the (-1, -1) skip move, are unchanged.
+    pub fn transform_move(self, m: (i8, i8)) -> (i8, i8) {
+        let (x, y) = m;
+        if !(0..8).contains(&x) || !(0..8).contains(&y) {
+            return m;
+        }
+
+        match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (7 - y, x),
+            Symmetry::Rotate180 => (7 - x, 7 - y),
+            Symmetry::Rotate270 => (y, 7 - x),
+            Symmetry::FlipHorizontal => (7 - x, y),
+            Symmetry::FlipVertical => (x, 7 - y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::FlipAntiDiagonal => (7 - y, 7 - x),
+        }
+    }
+
+    /// Applies the transform to every square of a bitboard
+    pub fn transform_bits(self, bits: u64) -> u64 {
+        match self {
+            Symmetry::Identity => bits,
+            Symmetry::Rotate90 => rotate_90(bits),
+            Symmetry::Rotate180 => bits.reverse_bits(),
+            Symmetry::Rotate270 => flip_diagonal(flip_horizontal(bits)),
+            Symmetry::FlipHorizontal => flip_horizontal(bits),
+            Symmetry::FlipVertical => flip_vertical(bits),
+            Symmetry::FlipDiagonal => flip_diagonal(bits),
+            Symmetry::FlipAntiDiagonal => flip_diagonal(bits).reverse_bits(),
+        }
+    }
+}
+
+pub fn rotate_90(bits: u64) -> u64 {
+    flip_horizontal(flip_diagonal(bits))
+}
+
+/// Reverses the bits of each row
+pub fn flip_horizontal(bits: u64) -> u64 {
+    const K1: u64 = 0x5555555555555555;
+    const K2: u64 = 0x3333333333333333;
+    const K4: u64 = 0x0f0f0f0f0f0f0f0f;
+
+    let bits = ((bits >> 1) & K1) | ((bits & K1) << 1);
+    let bits = ((bits >> 2) & K2) | ((bits & K2) << 2);
+    ((bits >> 4) & K4) | ((bits & K4) << 4)
+}
+
+/// Reverses the order of the rows
+pub fn flip_vertical(bits: u64) -> u64 {
+    bits.swap_bytes()
+}
+
+/// Swaps rows and columns
+pub fn flip_diagonal(bits: u64) -> u64 {
+    const K1: u64 = 0x5500550055005500;
+    const K2: u64 = 0x3333000033330000;
+    const K4: u64 = 0x0f0f0f0f00000000;
+
+    let mut bits = bits;
+    let t = K4 & (bits ^ (bits << 28));
+    bits ^= t ^ (t >> 28);
+    let t = K2 & (bits ^ (bits << 14));
+    bits ^= t ^ (t >> 14);
+    let t = K1 & (bits ^ (bits << 7));
+    bits ^= t ^ (t >> 7);
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Moves each bit one square at a time with `transform_move`
+    fn transform_bits_slowly(symmetry: Symmetry, bits: u64) -> u64 {
+        let mut transformed = 0;
+        for y in 0..8 {
+            for x in 0..8 {
+                if (bits >> (y * 8 + x)) & 1 != 0 {
+                    let (tx, ty) = symmetry.transform_move((x, y));
+                    transformed |= 1u64 << (ty * 8 + tx);
+                }
+            }
+        }
+        transformed
+    }
+
+    #[test]
+    fn bit_transforms_match_move_transforms() {
+        let samples = [
+            0u64,
+            u64::MAX,
+            1,
+            1 << 63,
+            0x0000_0018_1800_0000,
+            0x0123_4567_89ab_cdef,
+            0x8040_2010_0804_0201,
+            0xff00_0000_0000_00f1,
+        ];
+
+        for symmetry in Symmetry::ALL {
+            for bits in samples {
+                assert_eq!(
+                    symmetry.transform_bits(bits),
+                    transform_bits_slowly(symmetry, bits),
+                    "{symmetry:?} of {bits:#018x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverses() {
+        for symmetry in Symmetry::ALL {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let m = symmetry.transform_move((x, y));
+                    assert_eq!(symmetry.inverse().transform_move(m), (x, y));
+                }
+            }
+        }
+
+        assert_eq!(Symmetry::Rotate90.transform_move((-1, -1)), (-1, -1));
+    }
+
+    #[test]
+    fn rotating_four_times() {
+        let bits = 0x0123_4567_89ab_cdef;
+        assert_eq!(rotate_90(rotate_90(rotate_90(rotate_90(bits)))), bits);
+        assert_eq!(rotate_90(rotate_90(bits)), bits.reverse_bits());
+    }
+}
@@ -0,0 +1,402 @@
+//! An evaluation learned by self-play with TD(λ), for `bots::td_bot`.
+//!
+//! Positions are judged by a linear function of a few features, squashed
+//! into a guess of the result between -1 (white wins) and 1 (black wins).
+//! The features are the disc difference on each class of squares, where
+//! squares that are mirror images of each other share a class, and the
+//! difference in mobility. Each stage of the game has its own weights, as
+//! squares and mobility matter differently in the opening and the endgame.
+//! The weights are learned in standard games. In Anti-Othello the discs away
+//! from the edges are counted for the opponent, as they are the ones the
+//! player wants to give away, while edges, corners and mobility keep their
+//! worth.
+//!
+//! After each self-play game the weights are moved so that the guess for
+//! each position is closer to the guesses for the positions after it and
+//! finally to the result, with later positions counting less by a factor of
+//! λ per move.
+
+#[cfg(feature = "native")]
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bots::{BotRunner, MakeMove, create_bot, td_bot},
+    error::OthelloError,
+    game::Game,
+    position::Position,
+    tournament,
+    variant::Variant,
+};
+
+/// Squares counted apart from each edge: squares further in share a class
+const CLASS_DEPTH: i8 = 4;
+/// Classes of squares, from the corners inwards
+pub const SQUARE_CLASSES: usize = 10;
+/// The square classes and mobility
+pub const FEATURES: usize = SQUARE_CLASSES + 1;
+/// Stages of the game, by how full the board is
+pub const STAGES: usize = 4;
+
+/// Random plies at the start of each match game, so that bots that always
+/// play the same moves don't play the same game every time
+const MATCH_OPENING_PLIES: usize = 4;
+
+/// Learned weights for each stage of the game
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Weights {
+    pub stages: [[f32; FEATURES]; STAGES],
+    /// Self-play games the weights were learned from
+    pub games: u64,
+}
+
+impl Weights {
+    /// Weights that know nothing, guessing a draw for every position
+    pub fn new() -> Weights {
+        Weights::default()
+    }
+
+    /// The guessed result for black, from -1 (white wins) to 1 (black wins)
+    pub fn value<B: Position>(&self, board: &B, variant: Variant) -> f32 {
+        let (stage, features) = features(board, variant);
+        dot(&self.stages[stage], &features).tanh()
+    }
+
+    /// The guessed result for `color`, from -1 (loses) to 1 (wins)
+    pub fn evaluate<B: Position>(&self, board: &B, color: bool, variant: Variant) -> f32 {
+        let value = self.value(board, variant);
+        if color { -value } else { value }
+    }
+
+    #[cfg(feature = "native")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    #[cfg(feature = "native")]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Weights> {
+        let text = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+/// The stage of the game and the features of `board`, counted for black
+fn features<B: Position>(board: &B, variant: Variant) -> (usize, [f32; FEATURES]) {
+    let size = board.size();
+    let last = size - 1;
+    let mut features = [0f32; FEATURES];
+    let mut filled = 0;
+
+    for y in 0..size {
+        for x in 0..size {
+            if !board.is_occupied(x, y) {
+                continue;
+            }
+            filled += 1;
+            let mut disc = if board.get_color(x, y) { -1f32 } else { 1f32 };
+            let on_edge = x == 0 || y == 0 || x == last || y == last;
+            if variant == Variant::AntiOthello && !on_edge {
+                disc = -disc;
+            }
+            features[square_class(x, y, size)] += disc;
+        }
+    }
+
+    let black_moves = board.get_all_valid_moves(false).len() as f32;
+    let white_moves = board.get_all_valid_moves(true).len() as f32;
+    features[SQUARE_CLASSES] = (black_moves - white_moves) / 4f32;
+
+    let squares = (size as usize).pow(2);
+    let stage = (filled * STAGES / squares).min(STAGES - 1);
+    (stage, features)
+}
+
+/// The class of a square: 0 for corners, then the squares next to them,
+/// counting inwards. Mirror images of a square share its class.
+fn square_class(x: i8, y: i8, size: i8) -> usize {
+    let from_edge = |i: i8| i.min(size - 1 - i).min(CLASS_DEPTH - 1) as usize;
+    let (near, far) = {
+        let (a, b) = (from_edge(x), from_edge(y));
+        (a.min(b), a.max(b))
+    };
+    far * (far + 1) / 2 + near
+}
+
+fn dot(weights: &[f32; FEATURES], features: &[f32; FEATURES]) -> f32 {
+    weights.iter().zip(features).map(|(w, f)| w * f).sum()
+}
+
+/// Settings for learning by self-play
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// How far the weights move after each game
+    pub learning_rate: f32,
+    /// How much the later positions of a game count, from 0 (only the
+    /// next position) to 1 (only the result)
+    pub lambda: f32,
+    /// Chance of playing a random move in self-play games, so that the bots
+    /// see positions they wouldn't choose
+    pub exploration: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            learning_rate: 0.002,
+            lambda: 0.7,
+            exploration: 0.1,
+        }
+    }
+}
+
+/// Learns weights by playing games against itself
+pub struct Trainer {
+    pub weights: Weights,
+    config: Config,
+}
+
+impl Trainer {
+    /// Carries on learning from `weights`, ex. loaded from a file
+    pub fn new(weights: Weights, config: Config) -> Trainer {
+        Trainer { weights, config }
+    }
+
+    /// Plays a game between two bots using the current weights, searching
+    /// one ply and sometimes exploring, then learns from it
+    pub fn play_game(&mut self) -> Result<(), OthelloError> {
+        let bot = || -> Box<dyn MakeMove> {
+            Box::new(td_bot::Bot::with_config(
+                self.weights.clone(),
+                td_bot::Config {
+                    depth: 1,
+                    exploration: self.config.exploration,
+                },
+            ))
+        };
+        let mut runner = BotRunner::new(Some(bot()), Some(bot()));
+        runner.run_game_to_end()?;
+
+        self.learn(&runner.game);
+        Ok(())
+    }
+
+    /// Moves the weights towards the positions and result of a finished
+    /// standard game, with TD(λ)
+    pub fn learn(&mut self, game: &Game) {
+        let mut positions = vec![game.start().clone()];
+        for ply in game.history() {
+            // skips don't change the position
+            if let Some((x, y)) = ply.m {
+                let mut board = positions.last().expect("starts with a position").clone();
+                board.try_place_chip(x, y, ply.color);
+                positions.push(board);
+            }
+        }
+        let result = match Variant::Standard.winner(game.board()) {
+            Some(false) => 1f32,
+            Some(true) => -1f32,
+            None => 0f32,
+        };
+
+        let mut traces = [[0f32; FEATURES]; STAGES];
+        let mut changes = [[0f32; FEATURES]; STAGES];
+        let last = positions.len() - 1;
+        for (i, board) in positions[..last].iter().enumerate() {
+            let (stage, features) = features(board, Variant::Standard);
+            let value = dot(&self.weights.stages[stage], &features).tanh();
+            let next = if i + 1 < last {
+                self.weights.value(&positions[i + 1], Variant::Standard)
+            } else {
+                result
+            };
+
+            for trace in traces.iter_mut().flatten() {
+                *trace *= self.config.lambda;
+            }
+            // the gradient of tanh(w · f) is (1 - value²) f
+            for (trace, feature) in traces[stage].iter_mut().zip(features) {
+                *trace += (1f32 - value * value) * feature;
+            }
+
+            let error = next - value;
+            for (change, trace) in changes.iter_mut().flatten().zip(traces.iter().flatten()) {
+                *change += self.config.learning_rate * error * trace;
+            }
+        }
+
+        for (weight, change) in self
+            .weights
+            .stages
+            .iter_mut()
+            .flatten()
+            .zip(changes.iter().flatten())
+        {
+            *weight += change;
+        }
+        self.weights.games += 1;
+    }
+}
+
+/// The results of a match, counted for the learning bot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl MatchResult {
+    /// Points per game, counting draws as half a win. 1 = won every game.
+    pub fn score(&self) -> f32 {
+        let games = self.wins + self.losses + self.draws;
+        if games == 0 {
+            return 0f32;
+        }
+        (self.wins as f32 + self.draws as f32 * 0.5f32) / games as f32
+    }
+}
+
+/// Plays `pairs` pairs of games between a `td_bot` using `weights` and the
+/// bot named `opponent`, to measure how far learning has got. Each pair
+/// starts from the same random opening, with the bots swapping colors.
+///
+/// Returns an error if there is no bot named `opponent`. Forfeits count as
+/// losses for the bot that forfeited.
+pub fn play_match(
+    weights: &Weights,
+    config: td_bot::Config,
+    opponent: &str,
+    pairs: u32,
+) -> Result<MatchResult, OthelloError> {
+    let mut result = MatchResult::default();
+    let mut rng = rand::rng();
+
+    for _ in 0..pairs {
+        let (start, Some(first)) = tournament::random_opening(MATCH_OPENING_PLIES, &mut rng) else {
+            continue;
+        };
+
+        for learner_color in [false, true] {
+            let learner: Box<dyn MakeMove> =
+                Box::new(td_bot::Bot::with_config(weights.clone(), config));
+            let other = create_bot(opponent)
+                .ok_or_else(|| OthelloError::UnknownBot(opponent.to_string()))?;
+            let (black, white) = if learner_color {
+                (other, learner)
+            } else {
+                (learner, other)
+            };
+
+            match tournament::play_game(black, white, &start, first) {
+                Some(winner) if winner == learner_color => result.wins += 1,
+                Some(_) => result.losses += 1,
+                None => result.draws += 1,
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn square_classes() {
+        assert_eq!(square_class(0, 0, 8), 0);
+        assert_eq!(square_class(7, 7, 8), 0);
+        assert_eq!(square_class(1, 0, 8), square_class(0, 6, 8));
+        assert_eq!(square_class(1, 1, 8), 2);
+        assert_eq!(square_class(3, 4, 8), SQUARE_CLASSES - 1);
+        // squares further in than the classes go share the innermost class
+        assert_eq!(square_class(4, 5, 10), SQUARE_CLASSES - 1);
+        assert_eq!(square_class(1, 2, 4), square_class(1, 1, 8));
+    }
+
+    #[test]
+    fn values_are_symmetric() {
+        let mut weights = Weights::new();
+        assert_eq!(weights.value(&Board::new(), Variant::Standard), 0f32);
+
+        // the middle squares only
+        weights.stages[0][SQUARE_CLASSES - 1] = 0.5f32;
+        let mut board = Board::new();
+        board.try_place_chip(5, 4, false);
+        let value = weights.evaluate(&board, false, Variant::Standard);
+        assert!(value > 0f32);
+        assert_eq!(weights.evaluate(&board, true, Variant::Standard), -value);
+
+        // in Anti-Othello the middle discs are wanted by the opponent
+        assert_eq!(
+            weights.evaluate(&board, false, Variant::AntiOthello),
+            -value
+        );
+    }
+
+    #[test]
+    fn anti_othello_keeps_corners() {
+        let mut weights = Weights::new();
+        for stage in &mut weights.stages {
+            stage[0] = 1f32;
+        }
+        let mut board = Board::new();
+        board.set(0, 0, false);
+        let standard = weights.evaluate(&board, false, Variant::Standard);
+        assert!(standard > 0f32);
+        assert_eq!(
+            weights.evaluate(&board, false, Variant::AntiOthello),
+            standard
+        );
+    }
+
+    #[test]
+    fn learns_corners() {
+        let mut trainer = Trainer::new(
+            Weights::new(),
+            Config {
+                learning_rate: 0.01,
+                ..Config::default()
+            },
+        );
+        for _ in 0..200 {
+            trainer.play_game().unwrap();
+        }
+
+        let weights = &trainer.weights;
+        assert_eq!(weights.games, 200);
+        // holding corners wins games late in the game
+        assert!(weights.stages[STAGES - 1][0] > 0f32);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn save_and_load() {
+        let mut weights = Weights::new();
+        weights.stages[1][3] = -0.25;
+        weights.games = 7;
+
+        let path = std::env::temp_dir().join(format!("td_weights_{}.json", std::process::id()));
+        weights.save(&path).unwrap();
+        assert_eq!(Weights::load(&path).unwrap(), weights);
+        fs::remove_file(&path).unwrap();
+
+        assert!(Weights::load(&path).is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let config = td_bot::Config::default();
+        let result = play_match(&Weights::new(), config, "random_bot", 2).unwrap();
+        assert_eq!(result.wins + result.losses + result.draws, 4);
+        assert!((0f32..=1f32).contains(&result.score()));
+
+        assert_eq!(
+            play_match(&Weights::new(), config, "no_such_bot", 1),
+            Err(OthelloError::UnknownBot("no_such_bot".to_string()))
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Round robins between bots, to measure how strong they are.
+//!
+//! Each round, every pair of bots plays two games from the same random
+//! opening, one with each color, so that bots that always play the same
+//! moves don't play the same game every round. Elo ratings are then fitted
+//! to all the results at once, which doesn't depend on the order the games
+//! were played in, unlike updating the ratings after each game.
+//!
+//! The openings and every bot's random choices come from one seed, so a
+//! round robin with the same bots, rounds and seed plays the same games.
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    board::Board,
+    bots::{BotRunner, MakeMove, create_bot},
+    error::OthelloError,
+    variant::Variant,
+};
+
+/// Random plies at the start of each pair of games
+pub const OPENING_PLIES: usize = 4;
+
+/// The bot all ratings are measured from, rated 0
+pub const ANCHOR: &str = "random_bot";
+
+/// Rounds of fitting the ratings to the results
+const FIT_ITERATIONS: u32 = 2000;
+
+/// A random opening of `plies` plies, or fewer if the game ends, and the
+/// color to play next. None if the game ended.
+pub fn random_opening(plies: usize, rng: &mut impl Rng) -> (Board, Option<bool>) {
+    let mut board = Board::new();
+    let mut turn = Some(false);
+    for _ in 0..plies {
+        let Some(color) = turn else {
+            break;
+        };
+        let moves = board.get_all_valid_moves(color);
+        let (x, y) = moves[rng.random_range(0..moves.len())];
+        board.try_place_chip(x, y, color);
+        turn = board.next_turn(color);
+    }
+    (board, turn)
+}
+
+/// Plays a standard game from `start` with `first` to move. Returns the
+/// winner, None for a draw. A bot that forfeits loses.
+pub fn play_game(
+    black: Box<dyn MakeMove>,
+    white: Box<dyn MakeMove>,
+    start: &Board,
+    first: bool,
+) -> Option<bool> {
+    let mut runner = BotRunner::from_start(
+        Some(black),
+        Some(white),
+        start.clone(),
+        first,
+        Variant::Standard,
+    );
+    match runner.run_game_to_end() {
+        Ok(()) => Variant::Standard.winner(runner.game.board()),
+        // the bot to move forfeited
+        Err(_) => runner.game.turn().map(|color| !color),
+    }
+}
+
+/// The results of a round robin
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standings {
+    pub names: Vec<String>,
+    /// `points[i][j]` is what bot i scored against bot j, counting a win as
+    /// 1 and a draw as 0.5
+    pub points: Vec<Vec<f32>>,
+    /// Games played between each pair of bots
+    pub games_per_pair: u32,
+}
+
+impl Standings {
+    /// Points per game for each bot, from 0 to 1
+    pub fn scores(&self) -> Vec<f32> {
+        let games = self.games_per_pair as f32 * (self.names.len() - 1) as f32;
+        self.points
+            .iter()
+            .map(|row| row.iter().sum::<f32>() / games)
+            .collect()
+    }
+
+    /// The Elo ratings that best explain the results, with `ANCHOR` at 0 if
+    /// it played. Each pair is counted as having drawn one more game, so
+    /// that a bot that won every game still gets a finite rating.
+    pub fn elos(&self) -> Vec<f32> {
+        let count = self.names.len();
+        let games = self.games_per_pair as f32 + 1f32;
+        let mut elos = vec![0f32; count];
+
+        for _ in 0..FIT_ITERATIONS {
+            for i in 0..count {
+                let mut expected = 0f32;
+                let mut actual = 0f32;
+                for j in (0..count).filter(|&j| j != i) {
+                    expected += games * expected_score(elos[i], elos[j]);
+                    actual += self.points[i][j] + 0.5;
+                }
+                // moves the rating by up to 400 for a whole round's points
+                elos[i] += 400f32 * (actual - expected) / (games * (count - 1) as f32);
+            }
+        }
+
+        let anchor = self
+            .names
+            .iter()
+            .position(|name| name == ANCHOR)
+            .map_or(0f32, |i| elos[i]);
+        elos.iter().map(|elo| elo - anchor).collect()
+    }
+}
+
+/// The chance of a player rated `elo` beating one rated `opponent`
+pub fn expected_score(elo: f32, opponent: f32) -> f32 {
+    1f32 / (1f32 + 10f32.powf((opponent - elo) / 400f32))
+}
+
+/// Plays `rounds` rounds between the bots named `names`, with the openings
+/// and the bots' random choices drawn from `seed`, calling `on_round` after
+/// each round.
+///
+/// Returns an error if one of the names isn't a bot.
+pub fn round_robin(
+    names: &[&str],
+    rounds: u32,
+    seed: u64,
+    mut on_round: impl FnMut(u32, &Standings),
+) -> Result<Standings, OthelloError> {
+    let bot =
+        |name: &str| create_bot(name).ok_or_else(|| OthelloError::UnknownBot(name.to_string()));
+    for name in names {
+        bot(name)?;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut standings = Standings {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        points: vec![vec![0f32; names.len()]; names.len()],
+        games_per_pair: 0,
+    };
+
+    for round in 1..=rounds {
+        for a in 0..names.len() {
+            for b in a + 1..names.len() {
+                // an opening that ends the game is drawn again, so that
+                // every pair plays the same number of games
+                let (start, first) = loop {
+                    if let (start, Some(first)) = random_opening(OPENING_PLIES, &mut rng) {
+                        break (start, first);
+                    }
+                };
+                for (black, white) in [(a, b), (b, a)] {
+                    let mut black_bot = bot(names[black])?;
+                    let mut white_bot = bot(names[white])?;
+                    black_bot.set_seed(rng.random());
+                    white_bot.set_seed(rng.random());
+                    let winner = play_game(black_bot, white_bot, &start, first);
+                    let black_points = match winner {
+                        Some(false) => 1f32,
+                        Some(true) => 0f32,
+                        None => 0.5f32,
+                    };
+                    standings.points[black][white] += black_points;
+                    standings.points[white][black] += 1f32 - black_points;
+                }
+            }
+        }
+        standings.games_per_pair += 2;
+        on_round(round, &standings);
+    }
+
+    Ok(standings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openings() {
+        let mut rng = rand::rng();
+        let (board, turn) = random_opening(OPENING_PLIES, &mut rng);
+        assert_eq!(
+            board.count_pieces(false) + board.count_pieces(true),
+            4 + OPENING_PLIES as u16
+        );
+        assert_eq!(turn, Some(false));
+    }
+
+    #[test]
+    fn fitted_elos() {
+        let standings = Standings {
+            names: vec!["a".to_string(), ANCHOR.to_string(), "b".to_string()],
+            points: vec![
+                vec![0.0, 15.0, 20.0],
+                vec![5.0, 0.0, 20.0],
+                vec![0.0, 0.0, 0.0],
+            ],
+            games_per_pair: 20,
+        };
+        let elos = standings.elos();
+        assert_eq!(elos[1], 0.0);
+        assert!(elos[0] > 100.0);
+        assert!(elos[2] < -300.0);
+        assert_eq!(standings.scores(), vec![35.0 / 40.0, 25.0 / 40.0, 0.0]);
+
+        // even results give even ratings
+        let even = Standings {
+            points: vec![vec![0.0, 2.0], vec![2.0, 0.0]],
+            names: vec!["a".to_string(), "b".to_string()],
+            games_per_pair: 4,
+        };
+        assert!(even.elos().iter().all(|elo| elo.abs() < 0.01));
+    }
+
+    #[test]
+    fn rounds() {
+        let mut rounds_seen = 0;
+        let names = ["random_bot", "edge_exclusive_bot"];
+        let standings = round_robin(&names, 2, 7, |round, _| {
+            rounds_seen = round;
+        })
+        .unwrap();
+        assert_eq!(rounds_seen, 2);
+        assert_eq!(standings.games_per_pair, 4);
+        let total: f32 = standings.points.iter().flatten().sum();
+        assert_eq!(total, 4.0);
+        assert_eq!(round_robin(&names, 2, 7, |_, _| {}).unwrap(), standings);
+
+        assert_eq!(
+            round_robin(&["random_bot", "no_such_bot"], 1, 0, |_, _| {}),
+            Err(OthelloError::UnknownBot("no_such_bot".to_string()))
+        );
+    }
+}
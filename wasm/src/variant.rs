@@ -0,0 +1,62 @@
+//! Rule variants. They share the board and moves, and differ in who wins.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::position::Position;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Variant {
+    /// The player with the most pieces wins
+    #[default]
+    Standard,
+    /// Reversi misère: the player with the fewest pieces wins
+    AntiOthello,
+}
+
+impl Variant {
+    /// The winner of a finished game. None for a draw.
+    pub fn winner<B: Position>(self, board: &B) -> Option<bool> {
+        let whites = board.count_pieces(true);
+        let blacks = board.count_pieces(false);
+        if whites == blacks {
+            return None;
+        }
+
+        match self {
+            Variant::Standard => Some(whites > blacks),
+            Variant::AntiOthello => Some(whites < blacks),
+        }
+    }
+
+    /// Pieces that count for `color`: its own pieces in the standard game,
+    /// the opponent's in Anti-Othello
+    pub fn disc_score<B: Position>(self, board: &B, color: bool) -> u16 {
+        match self {
+            Variant::Standard => board.count_pieces(color),
+            Variant::AntiOthello => board.count_pieces(!color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn winners() {
+        let mut board = Board::new();
+        assert_eq!(Variant::Standard.winner(&board), None);
+        assert_eq!(Variant::AntiOthello.winner(&board), None);
+
+        board.try_place_chip(5, 4, false);
+        assert_eq!(Variant::Standard.winner(&board), Some(false));
+        assert_eq!(Variant::AntiOthello.winner(&board), Some(true));
+
+        assert_eq!(Variant::Standard.disc_score(&board, false), 4);
+        assert_eq!(Variant::AntiOthello.disc_score(&board, false), 1);
+    }
+}
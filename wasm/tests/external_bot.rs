@@ -0,0 +1,61 @@
+//! Plays built-in bots against the `engine` binary through the external
+//! process bot.
+
+use std::time::Duration;
+
+use wasm::{
+    bots::{self, BotRunner, MakeMove, external_bot},
+    position::{AnyBoard, Position},
+    variant::Variant,
+};
+
+fn engine(bot: &str) -> Box<dyn MakeMove> {
+    Box::new(external_bot::Bot::new(env!("CARGO_BIN_EXE_engine"), &[bot]).unwrap())
+}
+
+#[test]
+fn plays_built_in_bots() {
+    let mut runner = BotRunner::new(
+        Some(engine("first_valid_bot")),
+        bots::create_bot("random_bot"),
+    );
+    runner.run_game_to_end().unwrap();
+    assert_eq!(runner.game.turn(), None);
+
+    // the engine plays the same moves as the bot it wraps
+    let mut runner = BotRunner::new(
+        bots::create_bot("first_valid_bot"),
+        Some(engine("first_valid_bot")),
+    );
+    let mut local = BotRunner::new(
+        bots::create_bot("first_valid_bot"),
+        bots::create_bot("first_valid_bot"),
+    );
+    runner.run_game_to_end().unwrap();
+    local.run_game_to_end().unwrap();
+    assert_eq!(runner.game.history(), local.game.history());
+}
+
+#[test]
+fn other_sizes_and_variants() {
+    let mut runner = BotRunner::from_start(
+        Some(engine("deep_heuristic_bot")),
+        bots::create_bot("edge_bot"),
+        AnyBoard::new(6).unwrap(),
+        false,
+        Variant::AntiOthello,
+    );
+    runner.run_game_to_end().unwrap();
+    assert_eq!(runner.game.board().size(), 6);
+    assert_eq!(runner.game.turn(), None);
+}
+
+#[test]
+fn time_limits() {
+    // debug builds search a few tens of milliseconds past their limit, which
+    // is more than the engine's margin at short limits
+    let mut bot = engine("deep_heuristic_bot");
+    bot.set_time_limit(Some(Duration::from_millis(600)));
+    let mut runner = BotRunner::new(Some(bot), bots::create_bot("random_bot"));
+    runner.run_game_to_end().unwrap();
+}
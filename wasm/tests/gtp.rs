@@ -0,0 +1,77 @@
+//! Runs the scripts in `tests/gtp` through the `gtp` binary and compares its
+//! responses with the `.out` file next to each script. The scripts use
+//! `first_valid_bot` so that the moves are always the same.
+
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use wasm::bots::BOT_NAMES;
+
+/// Runs the binary with the bot, writing `input` to its stdin
+fn run_gtp(bot: &str, input: &str) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gtp"))
+        .arg(bot)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the binary may already have exited, ex. for an unknown bot
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn scripts() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/gtp");
+    let mut scripts: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "gtp"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty());
+
+    for script in scripts {
+        let input = fs::read_to_string(&script).unwrap();
+        let expected = fs::read_to_string(script.with_extension("out")).unwrap();
+
+        let (success, output) = run_gtp("first_valid_bot", &input);
+        assert!(success, "{}", script.display());
+        assert_eq!(output, expected, "{}", script.display());
+    }
+}
+
+#[test]
+fn quits() {
+    let (success, output) = run_gtp("random_bot", "name\nquit\nname\n");
+    assert!(success);
+    assert_eq!(output, "= othello-rs\n\n=\n\n");
+}
+
+#[test]
+fn every_bot_plays() {
+    for &bot in BOT_NAMES {
+        let (success, output) = run_gtp(bot, "boardsize 6\ngenmove b\ngenmove w\n");
+        assert!(success, "{bot}");
+        let moves: Vec<&str> = output.lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(moves.len(), 3, "{bot}");
+        assert!(moves[1..].iter().all(|m| m.len() == 4), "{bot}: {output}");
+    }
+}
+
+#[test]
+fn unknown_bot() {
+    let (success, output) = run_gtp("no_such_bot", "name\n");
+    assert!(!success);
+    assert!(output.is_empty());
+}
@@ -0,0 +1,250 @@
+//! Plays games on a game server through the reference client and through
+//! raw connections that break the rules.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use wasm::{
+    bots, notation,
+    position::Position,
+    server::{ClientMessage, GameRecord, Server, ServerMessage, client::Client},
+    snapshot::GameResult,
+};
+
+/// Starts a server on a free port, returning its address and the records
+/// it logs
+fn start_server(time_limit: Duration) -> (SocketAddr, Arc<Mutex<Vec<GameRecord>>>) {
+    let server = Server::bind("127.0.0.1:0", time_limit).unwrap();
+    let addr = server.local_addr().unwrap();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let records = Arc::clone(&log);
+    thread::spawn(move || {
+        server
+            .serve(move |record| records.lock().unwrap().push(record.clone()))
+            .unwrap();
+    });
+    (addr, log)
+}
+
+fn new_game(opponent: &str, color: bool, size: Option<u8>) -> ClientMessage {
+    ClientMessage::NewGame {
+        name: "tester".to_string(),
+        opponent: opponent.to_string(),
+        color,
+        size,
+        variant: Default::default(),
+    }
+}
+
+/// Waits a moment for the server's thread to log a game it has just sent
+fn logged(log: &Mutex<Vec<GameRecord>>, count: usize) -> Vec<GameRecord> {
+    for _ in 0..100 {
+        if log.lock().unwrap().len() >= count {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    log.lock().unwrap().clone()
+}
+
+#[test]
+fn bots_play_to_the_end() {
+    let (addr, log) = start_server(Duration::from_secs(5));
+
+    let mut client = Client::connect(addr).unwrap();
+    let mut bot = bots::create_bot("first_valid_bot").unwrap();
+    let record = client
+        .play_bot(&new_game("random_bot", false, None), bot.as_mut())
+        .unwrap();
+
+    assert_eq!(record.black, "tester");
+    assert_eq!(record.white, "random_bot");
+    assert_eq!(record.forfeit, None);
+    let moves = record.moves.split(' ').filter(|m| *m != "pass").count();
+    assert_eq!(
+        moves + 4,
+        (record.black_count + record.white_count) as usize
+    );
+    let expected = match record.black_count.cmp(&record.white_count) {
+        std::cmp::Ordering::Greater => GameResult::BlackWins,
+        std::cmp::Ordering::Less => GameResult::WhiteWins,
+        std::cmp::Ordering::Equal => GameResult::Draw,
+    };
+    assert_eq!(record.result, expected);
+    assert_eq!(logged(&log, 1), vec![record]);
+}
+
+#[test]
+fn games_at_once() {
+    let (addr, log) = start_server(Duration::from_secs(5));
+
+    let games: Vec<_> = [(false, Some(6)), (true, Some(4)), (true, None)]
+        .into_iter()
+        .map(|(color, size)| {
+            thread::spawn(move || {
+                let mut bot = bots::create_bot("shallow_score_bot").unwrap();
+                let mut client = Client::connect(addr).unwrap();
+                client
+                    .play_bot(&new_game("edge_bot", color, size), bot.as_mut())
+                    .unwrap()
+            })
+        })
+        .collect();
+
+    for game in games {
+        let record = game.join().unwrap();
+        assert_eq!(record.forfeit, None);
+    }
+    assert_eq!(logged(&log, 3).len(), 3);
+}
+
+#[test]
+fn human_choices() {
+    let (addr, _) = start_server(Duration::from_secs(5));
+    let mut client = Client::connect(addr).unwrap();
+
+    // always the last valid move, checking that the board is the right size
+    let record = client
+        .play_game(
+            &new_game("first_valid_bot", true, Some(6)),
+            |board, color| {
+                assert_eq!(board.size(), 6);
+                *board.get_all_valid_moves(color).last().unwrap()
+            },
+        )
+        .unwrap();
+    assert_eq!(record.white, "tester");
+    assert_eq!(record.forfeit, None);
+}
+
+#[test]
+fn errors() {
+    let (addr, log) = start_server(Duration::from_secs(5));
+
+    let mut client = Client::connect(addr).unwrap();
+    let err = client
+        .play_bot(
+            &new_game("no_such_bot", false, None),
+            bots::create_bot("random_bot").unwrap().as_mut(),
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unknown bot: no_such_bot");
+
+    let mut client = Client::connect(addr).unwrap();
+    client
+        .send(&new_game("random_bot", false, Some(7)))
+        .unwrap();
+    assert_eq!(
+        client.receive().unwrap(),
+        ServerMessage::Error {
+            message: "Boards can't be 7x7".to_string()
+        }
+    );
+
+    let mut client = Client::connect(addr).unwrap();
+    client.send(&ClientMessage::Move { m: (5, 4) }).unwrap();
+    assert!(matches!(
+        client.receive().unwrap(),
+        ServerMessage::Error { .. }
+    ));
+    assert!(log.lock().unwrap().is_empty());
+}
+
+/// Starts a game as black on a raw connection and reads up to the first
+/// `yourTurn`
+fn raw_game(addr: SocketAddr) -> (BufReader<TcpStream>, TcpStream) {
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+
+    let json = serde_json::to_string(&new_game("random_bot", false, None)).unwrap();
+    writeln!(writer, "{json}").unwrap();
+    for expected in ["gameStarted", "yourTurn"] {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.contains(expected), "{line}");
+    }
+    (reader, writer)
+}
+
+fn game_over(reader: &mut BufReader<TcpStream>) -> GameRecord {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    match serde_json::from_str(&line).unwrap() {
+        ServerMessage::GameOver(record) => record,
+        message => panic!("{message:?}"),
+    }
+}
+
+#[test]
+fn forfeits() {
+    let (addr, log) = start_server(Duration::from_millis(300));
+
+    let (mut reader, mut writer) = raw_game(addr);
+    writeln!(writer, r#"{{"type":"move","move":[0,0]}}"#).unwrap();
+    let record = game_over(&mut reader);
+    assert_eq!(record.result, GameResult::WhiteWins);
+    assert_eq!(
+        record.forfeit.as_deref(),
+        Some("Invalid move a1: The move doesn't flip any pieces")
+    );
+
+    let (mut reader, mut writer) = raw_game(addr);
+    writeln!(writer, "f5").unwrap();
+    let record = game_over(&mut reader);
+    assert_eq!(record.forfeit.as_deref(), Some("Expected a move, not f5"));
+
+    let (mut reader, _writer) = raw_game(addr);
+    let record = game_over(&mut reader);
+    assert_eq!(record.result, GameResult::WhiteWins);
+    assert_eq!(record.forfeit.as_deref(), Some("Ran out of time"));
+    assert_eq!(record.moves, "");
+
+    let (reader, writer) = raw_game(addr);
+    drop((reader, writer));
+    let records = logged(&log, 4);
+    assert_eq!(records[3].forfeit.as_deref(), Some("Disconnected"));
+}
+
+#[test]
+fn moves_match_the_board() {
+    let (addr, _) = start_server(Duration::from_secs(5));
+    let mut client = Client::connect(addr).unwrap();
+    client
+        .send(&new_game("first_valid_bot", true, None))
+        .unwrap();
+
+    let mut bot = bots::create_bot("last_valid_bot").unwrap();
+    loop {
+        match client.receive().unwrap() {
+            ServerMessage::GameStarted { color, size, .. } => {
+                assert!(color);
+                assert_eq!(size, 8);
+            }
+            ServerMessage::YourTurn {
+                board,
+                legal_moves,
+                last_move,
+            } => {
+                let board = notation::parse_board(&board).unwrap();
+                assert_eq!(legal_moves, board.get_all_valid_moves(true));
+                if let Some((x, y)) = last_move {
+                    assert!(board.is_occupied(x, y) && !board.get_color(x, y));
+                }
+                let m = bot.make_move(&board, true);
+                client.send(&ClientMessage::Move { m }).unwrap();
+            }
+            ServerMessage::GameOver(record) => {
+                assert_eq!(record.forfeit, None);
+                break;
+            }
+            ServerMessage::Error { message } => panic!("{message}"),
+        }
+    }
+}